@@ -0,0 +1,61 @@
+//! xbooth command line tool, talks to a validator over JSON RPC
+
+use async_trait::async_trait;
+use solana_client::rpc_client::RpcClient;
+use solana_program::{hash::Hash, pubkey::Pubkey};
+use solana_sdk::{
+    account::Account, commitment_config::CommitmentConfig, transaction::Transaction,
+    transport::TransportError,
+};
+use xbooth::{
+    cli,
+    client::{ClientError, XBoothRpc},
+};
+
+/// RpcTransport sends the requests of the cli to a validator
+struct RpcTransport(RpcClient);
+
+#[async_trait]
+impl XBoothRpc for RpcTransport {
+    async fn get_account(&mut self, address: &Pubkey) -> Result<Option<Account>, ClientError> {
+        let response = self
+            .0
+            .get_account_with_commitment(address, self.0.commitment())
+            .map_err(TransportError::from)?;
+        Ok(response.value)
+    }
+
+    async fn get_slot(&mut self) -> Result<u64, ClientError> {
+        Ok(self.0.get_slot().map_err(TransportError::from)?)
+    }
+
+    async fn get_latest_blockhash(&mut self) -> Result<Hash, ClientError> {
+        Ok(self
+            .0
+            .get_latest_blockhash()
+            .map_err(TransportError::from)?)
+    }
+
+    async fn send_transaction(&mut self, transaction: Transaction) -> Result<(), ClientError> {
+        self.0
+            .send_and_confirm_transaction(&transaction)
+            .map_err(TransportError::from)?;
+        Ok(())
+    }
+}
+
+fn main() {
+    let matches = cli::app().get_matches();
+    let url = matches.value_of("url").unwrap_or_default().to_string();
+    let rpc = RpcTransport(RpcClient::new_with_commitment(
+        url,
+        CommitmentConfig::confirmed(),
+    ));
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    if let Err(err) = futures::executor::block_on(cli::run(&matches, rpc, &mut out)) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,442 @@
+//! Command line interface of the exchange booth program used by the `xbooth`
+//! binary. Commands run against any XBoothRpc, so they can be exercised with
+//! solana-program-test as well as a validator. Enabled by the `cli` feature.
+
+use std::io::Write;
+use std::str::FromStr;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use spl_token::state::Account as TokenAccount;
+use thiserror::Error;
+
+use crate::client::{BoothView, ClientError, XBoothClient, XBoothRpc};
+use crate::processor::exchange::ExchangeMode;
+use crate::processor::utils;
+
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error("Invalid {0}: {1}")]
+    InvalidArgument(&'static str, String),
+    #[error("Cannot read keypair {0}: {1}")]
+    Keypair(String, String),
+    #[error(transparent)]
+    Client(#[from] ClientError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// app describes the arguments and subcommands of the xbooth binary
+pub fn app<'a, 'b>() -> App<'a, 'b> {
+    let booth = Arg::with_name("booth")
+        .value_name("BOOTH")
+        .required(true)
+        .help("Address of the exchange booth");
+
+    App::new("xbooth")
+        .about("Operate exchange booths")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("url")
+                .long("url")
+                .global(true)
+                .value_name("URL")
+                .default_value("http://localhost:8899")
+                .help("JSON RPC url of the validator"),
+        )
+        .arg(
+            Arg::with_name("program_id")
+                .long("program-id")
+                .global(true)
+                .value_name("PUBKEY")
+                .help("Address of the exchange booth program"),
+        )
+        .arg(
+            Arg::with_name("keypair")
+                .long("keypair")
+                .global(true)
+                .value_name("FILE")
+                .help("Keypair of the admin or trader, pays for the transactions [default: ~/.config/solana/id.json]"),
+        )
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .global(true)
+                .help("Print the output as json"),
+        )
+        .subcommand(
+            SubCommand::with_name("init")
+                .about("Create an exchange booth and its vaults")
+                .arg(Arg::with_name("mint_x").value_name("MINT_X").required(true))
+                .arg(Arg::with_name("mint_y").value_name("MINT_Y").required(true))
+                .arg(
+                    Arg::with_name("rate_numerator")
+                        .value_name("RATE_NUMERATOR")
+                        .required(true)
+                        .help("One token X buys RATE_NUMERATOR / RATE_DENOMINATOR tokens Y"),
+                )
+                .arg(
+                    Arg::with_name("rate_denominator")
+                        .value_name("RATE_DENOMINATOR")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("fee_bps")
+                        .long("fee-bps")
+                        .value_name("BPS")
+                        .default_value("0")
+                        .help("Fee charged on the output of an exchange"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("deposit")
+                .about("Deposit tokens into the vault of the mint of the token account")
+                .arg(booth.clone())
+                .arg(
+                    Arg::with_name("token_account")
+                        .value_name("TOKEN_ACCOUNT")
+                        .required(true),
+                )
+                .arg(amount_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("withdraw")
+                .about("Withdraw tokens from the vault of the mint of the token account")
+                .arg(booth.clone())
+                .arg(
+                    Arg::with_name("token_account")
+                        .value_name("TOKEN_ACCOUNT")
+                        .required(true),
+                )
+                .arg(amount_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("exchange")
+                .about("Exchange the tokens of one token account for the other mint")
+                .arg(booth.clone())
+                .arg(
+                    Arg::with_name("from_token_account")
+                        .value_name("FROM_TOKEN_ACCOUNT")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("receiving_token_account")
+                        .value_name("RECEIVING_TOKEN_ACCOUNT")
+                        .required(true),
+                )
+                .arg(amount_arg())
+                .arg(
+                    Arg::with_name("exact_out")
+                        .long("exact-out")
+                        .help("AMOUNT is the amount to receive instead of the amount to send"),
+                )
+                .arg(
+                    Arg::with_name("min_amount_out")
+                        .long("min-amount-out")
+                        .value_name("AMOUNT")
+                        .default_value("0"),
+                )
+                .arg(
+                    Arg::with_name("max_amount_in")
+                        .long("max-amount-in")
+                        .value_name("AMOUNT")
+                        .default_value("18446744073709551615"),
+                )
+                .arg(
+                    Arg::with_name("expiry_slot")
+                        .long("expiry-slot")
+                        .value_name("SLOT"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("show")
+                .about("Show the state and balances of an exchange booth")
+                .arg(booth.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("set-rate")
+                .about("Change the exchange rate A/B")
+                .arg(booth.clone())
+                .arg(
+                    Arg::with_name("rate_numerator")
+                        .value_name("RATE_NUMERATOR")
+                        .required(true)
+                        .help("One token A buys RATE_NUMERATOR / RATE_DENOMINATOR tokens B"),
+                )
+                .arg(
+                    Arg::with_name("rate_denominator")
+                        .value_name("RATE_DENOMINATOR")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("close")
+                .about("Close the exchange booth and send the remaining tokens to the token accounts")
+                .arg(booth)
+                .arg(
+                    Arg::with_name("token_account_a")
+                        .value_name("TOKEN_ACCOUNT_A")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("token_account_b")
+                        .value_name("TOKEN_ACCOUNT_B")
+                        .required(true),
+                ),
+        )
+}
+
+fn amount_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("amount")
+        .value_name("AMOUNT")
+        .required(true)
+        .help("Amount in base units of the mint")
+}
+
+/// run executes the subcommand of matches against rpc and writes the result to out
+pub async fn run<R: XBoothRpc + Send>(
+    matches: &ArgMatches<'_>,
+    rpc: R,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    let program_id = pubkey_arg(matches, "program_id")?;
+    let mut client = XBoothClient::new(rpc, program_id);
+    let json = matches.is_present("json");
+
+    let output = match matches.subcommand() {
+        ("init", Some(args)) => {
+            let signer = keypair(matches)?;
+            let mint_x = pubkey_arg(args, "mint_x")?;
+            let mint_y = pubkey_arg(args, "mint_y")?;
+            let rate_numerator = u64_arg(args, "rate_numerator")?;
+            let rate_denominator = u64_arg(args, "rate_denominator")?;
+            let fee_bps = u64_arg(args, "fee_bps")?;
+            let fee_bps = u16::try_from(fee_bps)
+                .map_err(|_| CliError::InvalidArgument("fee_bps", fee_bps.to_string()))?;
+
+            // the rate of the booth is A/B, invert the rate X/Y if Y is mint A
+            let (xbooth_pda, _xbooth_bump_seed, mint_a, mint_b) =
+                utils::find_exchange_booth_address(&program_id, &signer.pubkey(), &mint_x, &mint_y);
+            let (rate_numerator, rate_denominator) = if mint_a == mint_x {
+                (rate_numerator, rate_denominator)
+            } else {
+                (rate_denominator, rate_numerator)
+            };
+            client
+                .initialize_exchange_booth(
+                    &signer,
+                    &mint_a,
+                    &mint_b,
+                    rate_numerator,
+                    rate_denominator,
+                    fee_bps,
+                )
+                .await?;
+            json!({
+                "booth": xbooth_pda.to_string(),
+                "mint_a": mint_a.to_string(),
+                "mint_b": mint_b.to_string(),
+                "rate_numerator": rate_numerator,
+                "rate_denominator": rate_denominator,
+                "fee_bps": fee_bps,
+            })
+        }
+        ("deposit", Some(args)) => {
+            let signer = keypair(matches)?;
+            let booth = client.booth_at(&pubkey_arg(args, "booth")?).await?;
+            let token_account = pubkey_arg(args, "token_account")?;
+            let amount = u64_arg(args, "amount")?;
+            let mint = token_account_mint(&mut client, &token_account).await?;
+            client
+                .deposit(&signer, &booth, &token_account, &mint, amount)
+                .await?;
+            json!({
+                "booth": booth.address.to_string(),
+                "mint": mint.to_string(),
+                "amount": amount,
+            })
+        }
+        ("withdraw", Some(args)) => {
+            let signer = keypair(matches)?;
+            let booth = client.booth_at(&pubkey_arg(args, "booth")?).await?;
+            let token_account = pubkey_arg(args, "token_account")?;
+            let amount = u64_arg(args, "amount")?;
+            let mint = token_account_mint(&mut client, &token_account).await?;
+            client
+                .withdraw(&signer, &booth, &token_account, &mint, amount)
+                .await?;
+            json!({
+                "booth": booth.address.to_string(),
+                "mint": mint.to_string(),
+                "amount": amount,
+            })
+        }
+        ("exchange", Some(args)) => {
+            let signer = keypair(matches)?;
+            let booth = client.booth_at(&pubkey_arg(args, "booth")?).await?;
+            let from_token_account = pubkey_arg(args, "from_token_account")?;
+            let receiving_token_account = pubkey_arg(args, "receiving_token_account")?;
+            let amount = u64_arg(args, "amount")?;
+            let mode = if args.is_present("exact_out") {
+                ExchangeMode::ExactOut {
+                    amount_out: amount,
+                    max_amount_in: u64_arg(args, "max_amount_in")?,
+                }
+            } else {
+                ExchangeMode::ExactIn {
+                    amount_in: amount,
+                    min_amount_out: u64_arg(args, "min_amount_out")?,
+                }
+            };
+            let expiry_slot = if args.is_present("expiry_slot") {
+                Some(u64_arg(args, "expiry_slot")?)
+            } else {
+                None
+            };
+            let mint_in = token_account_mint(&mut client, &from_token_account).await?;
+            let quote = booth.quote(&mint_in, mode)?;
+            client
+                .exchange(
+                    &signer,
+                    &booth,
+                    &from_token_account,
+                    &receiving_token_account,
+                    mode,
+                    expiry_slot,
+                )
+                .await?;
+            json!({
+                "booth": booth.address.to_string(),
+                "mint_in": mint_in.to_string(),
+                "amount_in": quote.amount_in,
+                "amount_out": quote.amount_out,
+                "fee": quote.fee,
+            })
+        }
+        ("show", Some(args)) => {
+            let booth = client.booth_at(&pubkey_arg(args, "booth")?).await?;
+            booth_json(&booth)
+        }
+        ("set-rate", Some(args)) => {
+            let signer = keypair(matches)?;
+            let booth = client.booth_at(&pubkey_arg(args, "booth")?).await?;
+            let rate_numerator = u64_arg(args, "rate_numerator")?;
+            let rate_denominator = u64_arg(args, "rate_denominator")?;
+            client
+                .set_exchange_rate(&signer, &booth, rate_numerator, rate_denominator)
+                .await?;
+            json!({
+                "booth": booth.address.to_string(),
+                "rate_numerator": rate_numerator,
+                "rate_denominator": rate_denominator,
+            })
+        }
+        ("close", Some(args)) => {
+            let signer = keypair(matches)?;
+            let booth = client.booth_at(&pubkey_arg(args, "booth")?).await?;
+            let token_account_a = pubkey_arg(args, "token_account_a")?;
+            let token_account_b = pubkey_arg(args, "token_account_b")?;
+            client
+                .close_exchange_booth(&signer, &booth, &token_account_a, &token_account_b)
+                .await?;
+            json!({
+                "booth": booth.address.to_string(),
+                "amount_a": booth.balance_a,
+                "amount_b": booth.balance_b,
+            })
+        }
+        (command, _) => return Err(CliError::InvalidArgument("command", command.to_string())),
+    };
+
+    print_output(out, &output, json)
+}
+
+/// booth_json lists the state and balances of the exchange booth
+fn booth_json(booth: &BoothView) -> Value {
+    json!({
+        "booth": booth.address.to_string(),
+        "admin": booth.booth.admin.to_string(),
+        "seed_admin": booth.booth.seed_admin.to_string(),
+        "mint_a": booth.mint_a.to_string(),
+        "mint_b": booth.mint_b.to_string(),
+        "vault_a": booth.booth.vault_a.to_string(),
+        "vault_b": booth.booth.vault_b.to_string(),
+        "decimals_a": booth.decimals_a,
+        "decimals_b": booth.decimals_b,
+        "balance_a": booth.balance_a,
+        "balance_b": booth.balance_b,
+        "available_a": booth.available_a(),
+        "available_b": booth.available_b(),
+        "rate_numerator": booth.booth.rate_numerator,
+        "rate_denominator": booth.booth.rate_denominator,
+        "oracle": booth.booth.oracle.to_string(),
+        "fee_bps": booth.booth.fee_bps,
+        "fees_a": booth.booth.fees_a,
+        "fees_b": booth.booth.fees_b,
+        "min_reserve_a": booth.booth.min_reserve_a,
+        "min_reserve_b": booth.booth.min_reserve_b,
+        "is_paused": booth.booth.is_paused,
+    })
+}
+
+/// print_output writes the output as one json object or one `key: value` line per field
+fn print_output(out: &mut dyn Write, output: &Value, json: bool) -> Result<(), CliError> {
+    if json {
+        writeln!(out, "{}", output)?;
+        return Ok(());
+    }
+    if let Value::Object(fields) = output {
+        for (key, value) in fields {
+            // strings are printed without the json quotes
+            match value.as_str() {
+                Some(value) => writeln!(out, "{}: {}", key, value)?,
+                None => writeln!(out, "{}: {}", key, value)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// token_account_mint returns the mint of a token account
+async fn token_account_mint<R: XBoothRpc + Send>(
+    client: &mut XBoothClient<R>,
+    token_account: &Pubkey,
+) -> Result<Pubkey, CliError> {
+    let account = client
+        .rpc
+        .get_account(token_account)
+        .await?
+        .ok_or(ClientError::AccountNotFound(*token_account))?;
+    let token_account_data = TokenAccount::unpack(&account.data)
+        .map_err(|_| ClientError::InvalidAccountData(*token_account))?;
+    Ok(token_account_data.mint)
+}
+
+fn pubkey_arg(matches: &ArgMatches<'_>, name: &'static str) -> Result<Pubkey, CliError> {
+    let value = matches.value_of(name).unwrap_or_default();
+    Pubkey::from_str(value).map_err(|_| CliError::InvalidArgument(name, value.to_string()))
+}
+
+fn u64_arg(matches: &ArgMatches<'_>, name: &'static str) -> Result<u64, CliError> {
+    let value = matches.value_of(name).unwrap_or_default();
+    value
+        .parse()
+        .map_err(|_| CliError::InvalidArgument(name, value.to_string()))
+}
+
+/// keypair reads the keypair given with --keypair or the default solana cli keypair
+fn keypair(matches: &ArgMatches<'_>) -> Result<Keypair, CliError> {
+    match matches.value_of("keypair") {
+        Some(path) => read_keypair(path),
+        None => {
+            let home = std::env::var("HOME").unwrap_or_default();
+            read_keypair(&format!("{}/.config/solana/id.json", home))
+        }
+    }
+}
+
+fn read_keypair(path: &str) -> Result<Keypair, CliError> {
+    read_keypair_file(path).map_err(|err| CliError::Keypair(path.to_string(), err.to_string()))
+}
@@ -0,0 +1,611 @@
+//! Off-chain client of the exchange booth program. Decodes exchange booths
+//! into a BoothView, quotes exchanges locally with the on-chain math and
+//! builds and signs a transaction for each XBoothIntruction. Enabled by
+//! the `client` feature, which leaves out the program entrypoint.
+
+use async_trait::async_trait;
+use borsh::BorshDeserialize;
+use solana_program::{
+    instruction::Instruction, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+};
+use solana_sdk::{
+    account::Account, hash::Hash, signature::Keypair, signer::Signer, transaction::Transaction,
+    transport::TransportError,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+use thiserror::Error;
+
+use crate::errors::XBoothError;
+use crate::instruction;
+use crate::processor::exchange::ExchangeMode;
+use crate::processor::utils;
+use crate::quote;
+use crate::state::{ExchangeBoothAccount, OracleAccount};
+
+pub use crate::quote::Quote;
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("Account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("Invalid account data in {0}")]
+    InvalidAccountData(Pubkey),
+    #[error("Program error: {0}")]
+    Program(#[from] ProgramError),
+    #[error("Transport error: {0}")]
+    Transport(#[from] TransportError),
+}
+
+impl From<XBoothError> for ClientError {
+    fn from(e: XBoothError) -> Self {
+        ClientError::Program(e.into())
+    }
+}
+
+/// XBoothRpc is the connection to a cluster used by the client. It can be
+/// implemented on top of an rpc client or the BanksClient of solana-program-test
+#[async_trait]
+pub trait XBoothRpc {
+    async fn get_account(&mut self, address: &Pubkey) -> Result<Option<Account>, ClientError>;
+    async fn get_slot(&mut self) -> Result<u64, ClientError>;
+    async fn get_latest_blockhash(&mut self) -> Result<Hash, ClientError>;
+    async fn send_transaction(&mut self, transaction: Transaction) -> Result<(), ClientError>;
+}
+
+/// BoothView is an exchange booth together with the balances of its vaults
+/// and the decimals of its mints
+#[derive(Clone, Debug)]
+pub struct BoothView {
+    pub address: Pubkey,
+    pub booth: ExchangeBoothAccount,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub decimals_a: u8,
+    pub decimals_b: u8,
+    pub balance_a: u64,
+    pub balance_b: u64,
+    /// oracle pricing the exchange booth, if any
+    pub oracle: Option<OracleAccount>,
+    /// slot the view was loaded at, used to check the staleness of the oracle
+    pub slot: u64,
+}
+
+impl BoothView {
+    /// decode builds the view from the raw accounts of the exchange booth
+    #[allow(clippy::too_many_arguments)]
+    pub fn decode(
+        address: &Pubkey,
+        booth_account: &Account,
+        vault_a_account: &Account,
+        vault_b_account: &Account,
+        mint_a_account: &Account,
+        mint_b_account: &Account,
+        oracle_account: Option<&Account>,
+        slot: u64,
+    ) -> Result<Self, ClientError> {
+        let booth = ExchangeBoothAccount::try_from_slice(&booth_account.data)
+            .map_err(|_| ClientError::InvalidAccountData(*address))?;
+        let vault_a = TokenAccount::unpack(&vault_a_account.data)
+            .map_err(|_| ClientError::InvalidAccountData(booth.vault_a))?;
+        let vault_b = TokenAccount::unpack(&vault_b_account.data)
+            .map_err(|_| ClientError::InvalidAccountData(booth.vault_b))?;
+        let mint_a = Mint::unpack(&mint_a_account.data)
+            .map_err(|_| ClientError::InvalidAccountData(vault_a.mint))?;
+        let mint_b = Mint::unpack(&mint_b_account.data)
+            .map_err(|_| ClientError::InvalidAccountData(vault_b.mint))?;
+        let oracle = oracle_account
+            .map(|account| {
+                OracleAccount::try_from_slice(&account.data)
+                    .map_err(|_| ClientError::InvalidAccountData(booth.oracle))
+            })
+            .transpose()?;
+
+        Ok(BoothView {
+            address: *address,
+            mint_a: vault_a.mint,
+            mint_b: vault_b.mint,
+            decimals_a: mint_a.decimals,
+            decimals_b: mint_b.decimals,
+            balance_a: vault_a.amount,
+            balance_b: vault_b.amount,
+            booth,
+            oracle,
+            slot,
+        })
+    }
+
+    /// available_a is the balance of vault A that exchanges can use
+    pub fn available_a(&self) -> u64 {
+        quote::available_amount(self.balance_a, self.booth.fees_a, self.booth.min_reserve_a)
+    }
+
+    /// available_b is the balance of vault B that exchanges can use
+    pub fn available_b(&self) -> u64 {
+        quote::available_amount(self.balance_b, self.booth.fees_b, self.booth.min_reserve_b)
+    }
+
+    /// other_mint returns the mint of the exchange booth that is not mint
+    fn other_mint(&self, mint: &Pubkey) -> Pubkey {
+        if *mint == self.mint_a {
+            self.mint_b
+        } else {
+            self.mint_a
+        }
+    }
+
+    /// rate returns the exchange rate A/B, either the fixed rate or the
+    /// oracle price if it is usable at the slot of the view
+    pub fn rate(&self) -> Result<(u64, u64), ClientError> {
+        if self.booth.oracle == Pubkey::default() {
+            return Ok((self.booth.rate_numerator, self.booth.rate_denominator));
+        }
+        let oracle = self
+            .oracle
+            .as_ref()
+            .ok_or(ClientError::AccountNotFound(self.booth.oracle))?;
+        utils::check_oracle_price(&self.booth, oracle, self.slot)?;
+        Ok(utils::oracle_rate(oracle)?)
+    }
+
+    /// quote computes the exchange of mint_in for the other mint the same
+    /// way the program does, including the slippage and liquidity checks
+    pub fn quote(&self, mint_in: &Pubkey, mode: ExchangeMode) -> Result<Quote, ClientError> {
+        if self.booth.is_paused {
+            return Err(XBoothError::BoothPaused.into());
+        }
+        let is_a_to_b = if *mint_in == self.mint_a {
+            true
+        } else if *mint_in == self.mint_b {
+            false
+        } else {
+            return Err(XBoothError::InvalidMint.into());
+        };
+
+        let (rate_numerator, rate_denominator) = self.rate()?;
+        let (rate_in_out_numerator, rate_in_out_denominator) =
+            quote::rate_in_out(rate_numerator, rate_denominator, is_a_to_b);
+        let (decimals_in, decimals_out) = if is_a_to_b {
+            (self.decimals_a, self.decimals_b)
+        } else {
+            (self.decimals_b, self.decimals_a)
+        };
+
+        let quote = quote::quote(
+            mode,
+            rate_in_out_numerator,
+            rate_in_out_denominator,
+            decimals_in,
+            decimals_out,
+            self.booth.fee_bps,
+        )
+        .map_err(XBoothError::from)?;
+        quote::check_slippage(mode, &quote).map_err(XBoothError::from)?;
+
+        let available_out = if is_a_to_b {
+            self.available_b()
+        } else {
+            self.available_a()
+        };
+        if quote.gross_amount_out() > available_out {
+            return Err(XBoothError::InsufficientLiquidity.into());
+        }
+        Ok(quote)
+    }
+}
+
+/// XBoothClient sends the instructions of the exchange booth program through
+/// an XBoothRpc. The signer of each instruction pays for its transaction
+pub struct XBoothClient<R> {
+    pub rpc: R,
+    pub program_id: Pubkey,
+}
+
+impl<R: XBoothRpc + Send> XBoothClient<R> {
+    pub fn new(rpc: R, program_id: Pubkey) -> Self {
+        XBoothClient { rpc, program_id }
+    }
+
+    /// account fetches an account that has to exist
+    async fn account(&mut self, address: &Pubkey) -> Result<Account, ClientError> {
+        self.rpc
+            .get_account(address)
+            .await?
+            .ok_or(ClientError::AccountNotFound(*address))
+    }
+
+    /// booth loads the exchange booth created by admin for the pair mint_x/mint_y
+    pub async fn booth(
+        &mut self,
+        admin: &Pubkey,
+        mint_x: &Pubkey,
+        mint_y: &Pubkey,
+    ) -> Result<BoothView, ClientError> {
+        let (xbooth_pda, _xbooth_bump_seed, _, _) =
+            utils::find_exchange_booth_address(&self.program_id, admin, mint_x, mint_y);
+        self.booth_at(&xbooth_pda).await
+    }
+
+    /// booth_at loads the exchange booth at address
+    pub async fn booth_at(&mut self, address: &Pubkey) -> Result<BoothView, ClientError> {
+        let booth_account = self.account(address).await?;
+        let booth = ExchangeBoothAccount::try_from_slice(&booth_account.data)
+            .map_err(|_| ClientError::InvalidAccountData(*address))?;
+        let vault_a_account = self.account(&booth.vault_a).await?;
+        let vault_b_account = self.account(&booth.vault_b).await?;
+        let mint_a = TokenAccount::unpack(&vault_a_account.data)
+            .map_err(|_| ClientError::InvalidAccountData(booth.vault_a))?
+            .mint;
+        let mint_b = TokenAccount::unpack(&vault_b_account.data)
+            .map_err(|_| ClientError::InvalidAccountData(booth.vault_b))?
+            .mint;
+        let mint_a_account = self.account(&mint_a).await?;
+        let mint_b_account = self.account(&mint_b).await?;
+        let oracle_account = if booth.oracle == Pubkey::default() {
+            None
+        } else {
+            self.rpc.get_account(&booth.oracle).await?
+        };
+        let slot = self.rpc.get_slot().await?;
+
+        BoothView::decode(
+            address,
+            &booth_account,
+            &vault_a_account,
+            &vault_b_account,
+            &mint_a_account,
+            &mint_b_account,
+            oracle_account.as_ref(),
+            slot,
+        )
+    }
+
+    /// send signs the instructions with the payer and the signers and
+    /// sends them in one transaction
+    pub async fn send(
+        &mut self,
+        instructions: &[Instruction],
+        payer: &Keypair,
+        signers: &[&Keypair],
+    ) -> Result<(), ClientError> {
+        let recent_blockhash = self.rpc.get_latest_blockhash().await?;
+        let mut keypairs = vec![payer];
+        keypairs.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &keypairs,
+            recent_blockhash,
+        );
+        self.rpc.send_transaction(transaction).await
+    }
+
+    pub async fn initialize_exchange_booth(
+        &mut self,
+        admin: &Keypair,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        rate_numerator: u64,
+        rate_denominator: u64,
+        fee_bps: u16,
+    ) -> Result<(), ClientError> {
+        let ix = instruction::initialize_exchange_booth(
+            &self.program_id,
+            &admin.pubkey(),
+            mint_a,
+            mint_b,
+            rate_numerator,
+            rate_denominator,
+            fee_bps,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+
+    pub async fn deposit(
+        &mut self,
+        admin: &Keypair,
+        booth: &BoothView,
+        token_account: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Result<(), ClientError> {
+        let ix = instruction::deposit(
+            &self.program_id,
+            &booth.booth.seed_admin,
+            &admin.pubkey(),
+            token_account,
+            mint,
+            &booth.other_mint(mint),
+            amount,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+
+    pub async fn withdraw(
+        &mut self,
+        admin: &Keypair,
+        booth: &BoothView,
+        token_account: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Result<(), ClientError> {
+        let ix = instruction::withdraw(
+            &self.program_id,
+            &booth.booth.seed_admin,
+            &admin.pubkey(),
+            token_account,
+            mint,
+            &booth.other_mint(mint),
+            amount,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+
+    /// exchange sends an Exchange or ExchangeExactOut instruction depending on
+    /// the mode. The oracle account is added if the booth is priced by an oracle
+    pub async fn exchange(
+        &mut self,
+        trader: &Keypair,
+        booth: &BoothView,
+        from_token_account: &Pubkey,
+        receiving_token_account: &Pubkey,
+        mode: ExchangeMode,
+        expiry_slot: Option<u64>,
+    ) -> Result<(), ClientError> {
+        let mut ix = match mode {
+            ExchangeMode::ExactIn {
+                amount_in,
+                min_amount_out,
+            } => instruction::exchange(
+                &self.program_id,
+                &booth.booth.seed_admin,
+                &trader.pubkey(),
+                from_token_account,
+                receiving_token_account,
+                &booth.mint_a,
+                &booth.mint_b,
+                amount_in,
+                min_amount_out,
+                expiry_slot,
+            ),
+            ExchangeMode::ExactOut {
+                amount_out,
+                max_amount_in,
+            } => instruction::exchange_exact_out(
+                &self.program_id,
+                &booth.booth.seed_admin,
+                &trader.pubkey(),
+                from_token_account,
+                receiving_token_account,
+                &booth.mint_a,
+                &booth.mint_b,
+                amount_out,
+                max_amount_in,
+                expiry_slot,
+            ),
+        };
+        if booth.booth.oracle != Pubkey::default() {
+            ix.accounts
+                .push(solana_program::instruction::AccountMeta::new_readonly(
+                    booth.booth.oracle,
+                    false,
+                ));
+        }
+        self.send(&[ix], trader, &[]).await
+    }
+
+    pub async fn close_exchange_booth(
+        &mut self,
+        admin: &Keypair,
+        booth: &BoothView,
+        token_account_a: &Pubkey,
+        token_account_b: &Pubkey,
+    ) -> Result<(), ClientError> {
+        let ix = instruction::close_exchange_booth(
+            &self.program_id,
+            &booth.booth.seed_admin,
+            &admin.pubkey(),
+            token_account_a,
+            token_account_b,
+            &booth.mint_a,
+            &booth.mint_b,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+
+    pub async fn set_exchange_rate(
+        &mut self,
+        admin: &Keypair,
+        booth: &BoothView,
+        rate_numerator: u64,
+        rate_denominator: u64,
+    ) -> Result<(), ClientError> {
+        let ix = instruction::set_exchange_rate(
+            &self.program_id,
+            &booth.booth.seed_admin,
+            &admin.pubkey(),
+            &booth.mint_a,
+            &booth.mint_b,
+            rate_numerator,
+            rate_denominator,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+
+    /// update_oracle_price publishes a price to the oracle of the admin for
+    /// the pair mint_x/mint_y
+    pub async fn update_oracle_price(
+        &mut self,
+        admin: &Keypair,
+        mint_x: &Pubkey,
+        mint_y: &Pubkey,
+        price: u64,
+        expo: i32,
+        conf: u64,
+    ) -> Result<(), ClientError> {
+        let ix = instruction::update_oracle_price(
+            &self.program_id,
+            &admin.pubkey(),
+            mint_x,
+            mint_y,
+            price,
+            expo,
+            conf,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+
+    pub async fn set_oracle(
+        &mut self,
+        admin: &Keypair,
+        booth: &BoothView,
+        oracle: &Pubkey,
+        max_staleness: u64,
+        max_confidence_bps: u16,
+    ) -> Result<(), ClientError> {
+        let ix = instruction::set_oracle(
+            &self.program_id,
+            &booth.booth.seed_admin,
+            &admin.pubkey(),
+            &booth.mint_a,
+            &booth.mint_b,
+            oracle,
+            max_staleness,
+            max_confidence_bps,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+
+    pub async fn set_fee(
+        &mut self,
+        admin: &Keypair,
+        booth: &BoothView,
+        fee_bps: u16,
+    ) -> Result<(), ClientError> {
+        let ix = instruction::set_fee(
+            &self.program_id,
+            &booth.booth.seed_admin,
+            &admin.pubkey(),
+            &booth.mint_a,
+            &booth.mint_b,
+            fee_bps,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+
+    pub async fn withdraw_fees(
+        &mut self,
+        admin: &Keypair,
+        booth: &BoothView,
+        token_account_a: &Pubkey,
+        token_account_b: &Pubkey,
+    ) -> Result<(), ClientError> {
+        let ix = instruction::withdraw_fees(
+            &self.program_id,
+            &booth.booth.seed_admin,
+            &admin.pubkey(),
+            token_account_a,
+            token_account_b,
+            &booth.mint_a,
+            &booth.mint_b,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+
+    pub async fn set_min_reserves(
+        &mut self,
+        admin: &Keypair,
+        booth: &BoothView,
+        min_reserve_a: u64,
+        min_reserve_b: u64,
+    ) -> Result<(), ClientError> {
+        let ix = instruction::set_min_reserves(
+            &self.program_id,
+            &booth.booth.seed_admin,
+            &admin.pubkey(),
+            &booth.mint_a,
+            &booth.mint_b,
+            min_reserve_a,
+            min_reserve_b,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+
+    /// migrate_booth migrates a booth in the V1 layout, which cannot be
+    /// loaded into a BoothView before it is migrated. The V1 layout has no
+    /// rate and fee, the rate A/B and the fee are set by the migration.
+    /// mint_a and mint_b are given in the order the booth was created with
+    pub async fn migrate_booth(
+        &mut self,
+        admin: &Keypair,
+        mint_a: &Pubkey,
+        mint_b: &Pubkey,
+        rate_numerator: u64,
+        rate_denominator: u64,
+        fee_bps: u16,
+    ) -> Result<(), ClientError> {
+        let ix = instruction::migrate_booth(
+            &self.program_id,
+            &admin.pubkey(),
+            mint_a,
+            mint_b,
+            rate_numerator,
+            rate_denominator,
+            fee_bps,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+
+    pub async fn propose_admin(
+        &mut self,
+        admin: &Keypair,
+        booth: &BoothView,
+        new_admin: &Pubkey,
+    ) -> Result<(), ClientError> {
+        let ix = instruction::propose_admin(
+            &self.program_id,
+            &booth.booth.seed_admin,
+            &admin.pubkey(),
+            &booth.mint_a,
+            &booth.mint_b,
+            new_admin,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+
+    pub async fn accept_admin(
+        &mut self,
+        new_admin: &Keypair,
+        booth: &BoothView,
+    ) -> Result<(), ClientError> {
+        let ix = instruction::accept_admin(
+            &self.program_id,
+            &booth.booth.seed_admin,
+            &new_admin.pubkey(),
+            &booth.mint_a,
+            &booth.mint_b,
+        );
+        self.send(&[ix], new_admin, &[]).await
+    }
+
+    pub async fn pause(&mut self, admin: &Keypair, booth: &BoothView) -> Result<(), ClientError> {
+        let ix = instruction::pause(
+            &self.program_id,
+            &booth.booth.seed_admin,
+            &admin.pubkey(),
+            &booth.mint_a,
+            &booth.mint_b,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+
+    pub async fn unpause(&mut self, admin: &Keypair, booth: &BoothView) -> Result<(), ClientError> {
+        let ix = instruction::unpause(
+            &self.program_id,
+            &booth.booth.seed_admin,
+            &admin.pubkey(),
+            &booth.mint_a,
+            &booth.mint_b,
+        );
+        self.send(&[ix], admin, &[]).await
+    }
+}
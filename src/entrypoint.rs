@@ -1,7 +1,7 @@
 use crate::errors::XBoothError;
 use crate::processor::Processor;
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::PrintProgramError,
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::PrintProgramError,
     pubkey::Pubkey,
 };
 
@@ -13,7 +13,7 @@ fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    msg!(
+    verbose_msg!(
         "process_instruction: {}: {} accounts, data={:?}",
         program_id,
         accounts.len(),
@@ -4,6 +4,8 @@ use solana_program::{
 };
 use thiserror::Error;
 
+use crate::quote::QuoteError;
+
 #[derive(Error, Debug, Clone, Eq, Copy, FromPrimitive, PartialEq)]
 pub enum XBoothError {
     #[error("Invalid Account address.")]
@@ -28,6 +30,49 @@ pub enum XBoothError {
     InvalidMint,
     #[error("Accounts cannot have the same mint")]
     UniqueMintAccounts,
+    #[error("Amount overflow")]
+    AmountOverflow,
+    #[error("Invalid exchange rate")]
+    InvalidExchangeRate,
+    #[error("Invalid oracle account")]
+    InvalidOracleAccount,
+    #[error("Oracle price is stale")]
+    StaleOraclePrice,
+    #[error("Oracle confidence interval is too wide")]
+    OracleConfidenceTooWide,
+    #[error("Unsupported instruction version")]
+    UnsupportedInstructionVersion,
+    #[error("Invalid fee")]
+    InvalidFee,
+    #[error("Exchange output is below the minimum amount")]
+    SlippageExceeded,
+    #[error("Exchange has expired")]
+    TradeExpired,
+    #[error("Insufficient liquidity in vault")]
+    InsufficientLiquidity,
+    #[error("Unsupported account version")]
+    InvalidAccountVersion,
+    #[error("Account is already initialized")]
+    AlreadyInitialized,
+    #[error("Invalid token program")]
+    InvalidTokenProgram,
+    #[error("Mint A and mint B cannot be the same mint")]
+    IdenticalMints,
+    #[error("Mint A must be ordered before mint B")]
+    NonCanonicalMintOrder,
+    #[error("Exchange booth is paused")]
+    BoothPaused,
+}
+
+impl From<QuoteError> for XBoothError {
+    fn from(e: QuoteError) -> Self {
+        match e {
+            QuoteError::AmountOverflow => XBoothError::AmountOverflow,
+            QuoteError::InvalidExchangeRate => XBoothError::InvalidExchangeRate,
+            QuoteError::InvalidFee => XBoothError::InvalidFee,
+            QuoteError::SlippageExceeded => XBoothError::SlippageExceeded,
+        }
+    }
 }
 
 impl From<XBoothError> for ProgramError {
@@ -0,0 +1,125 @@
+//! Events logged by the exchange booth program. Each event is logged with
+//! `sol_log_data` as the borsh serialized XBoothEvent, so indexers can read
+//! the amounts of an action from the log messages of its transaction.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{entrypoint::ProgramResult, log::sol_log_data, pubkey::Pubkey};
+
+/// prefix of the log messages written by sol_log_data
+const PROGRAM_DATA_LOG_PREFIX: &str = "Program data: ";
+
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum XBoothEvent {
+    /// An exchange booth was created with empty vaults
+    BoothInitialized {
+        booth: Pubkey,
+        admin: Pubkey,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        vault_a: Pubkey,
+        vault_b: Pubkey,
+        rate_numerator: u64,
+        rate_denominator: u64,
+        fee_bps: u16,
+    },
+    /// The admin moved amount from the token account into the vault
+    Deposited {
+        booth: Pubkey,
+        mint: Pubkey,
+        vault: Pubkey,
+        token_account: Pubkey,
+        amount: u64,
+    },
+    /// The admin moved amount from the vault into the token account
+    Withdrawn {
+        booth: Pubkey,
+        mint: Pubkey,
+        vault: Pubkey,
+        token_account: Pubkey,
+        amount: u64,
+    },
+    /// The trader sent amount_in of mint_in and received amount_out of
+    /// mint_out, the fee stays in the vault of mint_out
+    Exchanged {
+        booth: Pubkey,
+        trader: Pubkey,
+        mint_in: Pubkey,
+        mint_out: Pubkey,
+        amount_in: u64,
+        amount_out: u64,
+        fee: u64,
+    },
+    /// The admin set the exchange rate A/B
+    RateChanged {
+        booth: Pubkey,
+        rate_numerator: u64,
+        rate_denominator: u64,
+    },
+    /// The admin withdrew the collected fees amount_a and amount_b
+    FeesWithdrawn {
+        booth: Pubkey,
+        admin: Pubkey,
+        amount_a: u64,
+        amount_b: u64,
+    },
+    /// The admin closed the exchange booth and received the remaining
+    /// amount_a and amount_b of the vaults
+    Closed {
+        booth: Pubkey,
+        admin: Pubkey,
+        amount_a: u64,
+        amount_b: u64,
+    },
+}
+
+impl XBoothEvent {
+    /// emit logs the event as program data
+    pub fn emit(&self) -> ProgramResult {
+        let data = self.try_to_vec()?;
+        sol_log_data(&[&data]);
+        Ok(())
+    }
+}
+
+/// parse_events returns the events emitted by the program in the log messages
+/// of a transaction. Program data logged while another program is invoked,
+/// e.g. by the token program during a cross program invocation, is skipped
+pub fn parse_events(program_id: &Pubkey, log_messages: &[String]) -> Vec<XBoothEvent> {
+    let program_id = program_id.to_string();
+    let mut invoked_programs: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for log_message in log_messages {
+        if let Some(data) = log_message.strip_prefix(PROGRAM_DATA_LOG_PREFIX) {
+            if invoked_programs.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            for field in data.split(' ') {
+                let event = base64::decode(field)
+                    .ok()
+                    .and_then(|bytes| XBoothEvent::try_from_slice(&bytes).ok());
+                if let Some(event) = event {
+                    events.push(event);
+                }
+            }
+            continue;
+        }
+
+        // track the program stack with the "Program <id> invoke [<depth>]"
+        // and "Program <id> success" or "Program <id> failed: <error>" logs.
+        // Program ids never end with a colon unlike "Program log:"
+        let mut words = log_message.split(' ');
+        match (words.next(), words.next(), words.next()) {
+            (Some("Program"), Some(program), Some("invoke")) if !program.ends_with(':') => {
+                invoked_programs.push(program)
+            }
+            (Some("Program"), Some(program), Some("success" | "failed:"))
+                if !program.ends_with(':') =>
+            {
+                invoked_programs.pop();
+            }
+            _ => {}
+        }
+    }
+    events
+}
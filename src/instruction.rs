@@ -1,4 +1,22 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+use crate::errors::XBoothError;
+use crate::processor::utils::{
+    find_exchange_booth_address, find_oracle_address, find_vault_address,
+};
+
+/// INSTRUCTION_VERSION is the first byte of the instruction data.
+/// Legacy instructions carried f64 amounts and started directly with the
+/// instruction tag, which never has the high bit set, so they can never be
+/// mistaken for a versioned instruction.
+pub const INSTRUCTION_VERSION: u8 = 0x80 | 2;
 
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub enum XBoothIntruction {
@@ -18,17 +36,32 @@ pub enum XBoothIntruction {
     ///     - is_writable: false,
     /// 4. vault A
     ///     - is_signer: false,
-    ///     - is_writable: false
+    ///     - is_writable: true
     /// 5. vault B
     ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 6. mint A: the mint with the smaller pubkey of the pair
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 7. mint B: the mint with the larger pubkey of the pair
+    ///     - is_signer: false,
     ///     - is_writable: false
-    /// 6. token program A
+    /// 8. token program: the spl_token program
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 9. rent sysvar
     ///     - is_signer: false,
     ///     - is_writable: false
     ///
     /// instruction_data
-    /// amount: amount of tokens of x that should be deposited
-    InitializeExhangeBooth {},
+    /// rate_numerator, rate_denominator: the exchange rate A/B, one token A
+    /// buys rate_numerator / rate_denominator tokens B
+    /// fee_bps: fee charged on the output of an exchange in basis points
+    InitializeExhangeBooth {
+        rate_numerator: u64,
+        rate_denominator: u64,
+        fee_bps: u16,
+    },
     /// Deposit
     /// allows the booth admin to deposit tokens into one of the vaults
     /// from the booth
@@ -55,7 +88,10 @@ pub enum XBoothIntruction {
     /// 7. token program: The spl_token program
     ///     - is_signer: false,
     ///     - is_writable: false
-    Deposit { amount: f64 },
+    ///
+    /// instruction_data
+    /// amount: amount of tokens to deposit, in base units of the mint
+    Deposit { amount: u64 },
     /// Withdraw
     /// should allow the owner of the exchange booth to
     /// withdraw from any of the vaults and transfer it to a token account
@@ -67,10 +103,10 @@ pub enum XBoothIntruction {
     /// 2. authority: the signer of the transaction, owner of token_account
     ///     - is_signer: true,
     ///     - is_writable: false
-    /// 3. token_account: account holding tokens from mint A
+    /// 3. token_account: account receiving tokens of the mint of the vault
     ///     - is_signer: false,
     ///     - is_writable: true,
-    /// 4. vault A: pda, vault that can hold mint A
+    /// 4. vault: pda, vault A or vault B of the exchange booth
     ///     - is_signer:false,
     ///     - is_writable: true
     /// 5. mint A: the mint account of Token A
@@ -82,38 +118,922 @@ pub enum XBoothIntruction {
     /// 7. token program: The spl_token program
     ///     - is_signer: false,
     ///     - is_writable: false
-    Withdraw { amount: f64 },
+    ///
+    /// instruction_data
+    /// amount: amount of tokens to withdraw, in base units of the mint
+    Withdraw { amount: u64 },
     /// Exchange tokens
     /// should allow anybody to exchange token A for token B at an exchange rate A/B
+    /// or token B for token A at the inverse rate. The direction is decided by
+    /// the mints of the from and receiving token accounts. The booth fee is
+    /// charged on the output and kept in the vault
     ///
     /// Accounts:
     /// 1. Exchange_booth_account: pda
     ///     - is_signer:false,
-    ///     - is_writable: false
-    /// 2. authority: signer of the transaction, owner of the token_account
+    ///     - is_writable: true
+    /// 2. trader: signer of the transaction, owner of the from_token_account.
+    ///    Does not need to be the admin of the exchange booth
     ///     - is_signer:true,
     ///     - is_writable: false,
-    /// 3. to_token_account: token to withdraw and deposit into
+    /// 3. from_token_account: token account to send tokens from, mint A or mint B
     ///     - is_signer: false,
     ///     - is_writable: true,
-    /// 4. from_token_account: token to withdraw and deposit into
+    /// 4. receiving_token_account: token account to receive tokens in, the other mint
     ///     - is_signer: false,
     ///     - is_writable: true,
-    /// 4. vault A: pda
+    /// 5. vault A: pda
     ///     - is_signer: false,
     ///     - is_writable:true
-    /// 5. vault B: pda
+    /// 6. vault B: pda
     ///     - is_signer: false,
     ///     - is_writable: true,
-    /// 6. mint_a: mint account for token A
+    /// 7. mint_a: mint account for token A
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 8. mint_b: mint account for token B
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 9. token_program: the spl_token program
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 10. oracle account: only if the exchange booth is priced by an oracle
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    ///
+    /// instruction_data
+    /// amount: amount of tokens to send from the from_token_account, in base units of the mint
+    /// min_amount_out: the exchange fails if less tokens would be received
+    /// expiry_slot: the exchange fails if it is processed after this slot
+    Exchange {
+        amount: u64,
+        min_amount_out: u64,
+        expiry_slot: Option<u64>,
+    },
+    /// Close Exchange booth
+    /// allows the booth admin to close the exchange booth. Remaining tokens
+    /// in the vaults are transferred to the given token accounts, the vaults
+    /// are closed and the rent is returned to the admin
+    ///
+    /// Accounts:
+    /// 1. exchange_booth_account: pda
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 2. authority: the signer of the transaction, admin of the exchange booth
+    ///     - is_signer: true,
+    ///     - is_writable: true
+    /// 3. vault A: pda, vault that holds mint A
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 4. vault B: pda, vault that holds mint B
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 5. token_account_a: receives the remaining tokens in vault A
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 6. token_account_b: receives the remaining tokens in vault B
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 7. mint A: the mint account of Token A
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 8. mint B: the mint account of Token B
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 9. token program: The spl_token program
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    CloseExchangeBooth {},
+    /// Set Exchange rate
+    /// allows the booth admin to change the exchange rate A/B
+    ///
+    /// Accounts:
+    /// 1. exchange_booth_account: pda
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 2. authority: the signer of the transaction, admin of the exchange booth
+    ///     - is_signer: true,
+    ///     - is_writable: false
+    /// 3. mint A: the mint account of Token A
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 4. mint B: the mint account of Token B
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    ///
+    /// instruction_data
+    /// rate_numerator, rate_denominator: the new exchange rate A/B
+    SetExchangeRate {
+        rate_numerator: u64,
+        rate_denominator: u64,
+    },
+    /// Update Oracle price
+    /// publishes a new price for the pair A/B. The oracle account is created
+    /// on the first update and only its admin can update it afterwards
+    ///
+    /// Accounts:
+    /// 1. oracle_account: pda
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 2. authority: the signer of the transaction, admin of the oracle
+    ///     - is_signer: true,
+    ///     - is_writable: true
+    /// 3. mint A: the mint account of Token A
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 4. mint B: the mint account of Token B
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 5. system_program
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    ///
+    /// instruction_data
+    /// price, expo: one token A is worth price * 10^expo tokens B
+    /// conf: confidence interval of the price
+    UpdateOraclePrice { price: u64, expo: i32, conf: u64 },
+    /// Set Oracle
+    /// allows the booth admin to price exchanges with an oracle. Setting
+    /// the oracle to the default pubkey goes back to the fixed exchange rate
+    ///
+    /// Accounts:
+    /// 1. exchange_booth_account: pda
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 2. authority: the signer of the transaction, admin of the exchange booth
+    ///     - is_signer: true,
+    ///     - is_writable: false
+    /// 3. mint A: the mint account of Token A
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 4. mint B: the mint account of Token B
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    ///
+    /// instruction_data
+    /// oracle: the oracle account the booth trusts
+    /// max_staleness: max number of slots since the price was published
+    /// max_confidence_bps: max confidence interval in basis points of the price
+    SetOracle {
+        oracle: Pubkey,
+        max_staleness: u64,
+        max_confidence_bps: u16,
+    },
+    /// Set Fee
+    /// allows the booth admin to change the fee charged on exchanges
+    ///
+    /// Accounts:
+    /// 1. exchange_booth_account: pda
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 2. authority: the signer of the transaction, admin of the exchange booth
+    ///     - is_signer: true,
+    ///     - is_writable: false
+    /// 3. mint A: the mint account of Token A
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 4. mint B: the mint account of Token B
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    ///
+    /// instruction_data
+    /// fee_bps: fee charged on the output of an exchange in basis points
+    SetFee { fee_bps: u16 },
+    /// Withdraw Fees
+    /// allows the booth admin to withdraw the fees collected in both vaults
+    ///
+    /// Accounts:
+    /// 1. exchange_booth_account: pda
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 2. authority: the signer of the transaction, admin of the exchange booth
+    ///     - is_signer: true,
+    ///     - is_writable: false
+    /// 3. vault A: pda, vault that holds mint A
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 4. vault B: pda, vault that holds mint B
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 5. token_account_a: receives the fees collected in vault A
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 6. token_account_b: receives the fees collected in vault B
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 7. mint A: the mint account of Token A
     ///     - is_signer: false,
     ///     - is_writable: false
-    /// 7. mint_b: mint account for token B
+    /// 8. mint B: the mint account of Token B
     ///     - is_signer: false,
     ///     - is_writable: false
-    /// 8. token_program: the spl_token program
+    /// 9. token program: The spl_token program
     ///     - is_signer: false,
     ///     - is_writable: false
-    /// (9. oracle account)
-    Exchange { amount: f64 },
+    WithdrawFees {},
+    /// Exchange tokens for an exact output
+    /// same as Exchange, but the trader fixes the amount to receive. The
+    /// required input is computed from the exchange rate, the booth fee and the
+    /// mint decimals and rounded up in favor of the booth
+    ///
+    /// Accounts: same as Exchange
+    ///
+    /// instruction_data
+    /// amount_out: amount of tokens to receive in the receiving_token_account, in base units of the mint
+    /// max_amount_in: the exchange fails if more tokens would be sent
+    /// expiry_slot: the exchange fails if it is processed after this slot
+    ExchangeExactOut {
+        amount_out: u64,
+        max_amount_in: u64,
+        expiry_slot: Option<u64>,
+    },
+    /// Set Min Reserves
+    /// allows the booth admin to keep a min balance in each vault that
+    /// exchanges cannot use
+    ///
+    /// Accounts:
+    /// 1. exchange_booth_account: pda
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 2. authority: the signer of the transaction, admin of the exchange booth
+    ///     - is_signer: true,
+    ///     - is_writable: false
+    /// 3. mint A: the mint account of Token A
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 4. mint B: the mint account of Token B
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    ///
+    /// instruction_data
+    /// min_reserve_a: min balance of vault A, in base units of mint A
+    /// min_reserve_b: min balance of vault B, in base units of mint B
+    SetMinReserves {
+        min_reserve_a: u64,
+        min_reserve_b: u64,
+    },
+    /// Migrate Booth
+    /// upgrades an exchange booth created with the V1 layout to the current
+    /// layout in place. The account is reallocated and the admin tops up the
+    /// rent
+    ///
+    /// Accounts:
+    /// 1. exchange_booth_account: pda
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 2. authority: the signer of the transaction, admin of the exchange booth
+    ///     - is_signer: true,
+    ///     - is_writable: true
+    /// 3. mint A: the mint account of Token A
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 4. mint B: the mint account of Token B
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 5. system_program: pays the additional rent
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    ///
+    /// instruction_data
+    /// rate_numerator, rate_denominator: exchange rate A/B, the V1 layout
+    /// does not store a rate
+    /// fee_bps: fee charged on the output of an exchange in basis points
+    MigrateBooth {
+        rate_numerator: u64,
+        rate_denominator: u64,
+        fee_bps: u16,
+    },
+    /// Propose Admin
+    /// proposes a new admin for the exchange booth. The handover completes
+    /// once the proposed admin accepts it with AcceptAdmin
+    ///
+    /// Accounts:
+    /// 1. exchange_booth_account: pda
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 2. authority: the signer of the transaction, admin of the exchange booth
+    ///     - is_signer: true,
+    ///     - is_writable: false
+    /// 3. mint A: the mint account of Token A
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 4. mint B: the mint account of Token B
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    ///
+    /// instruction_data
+    /// new_admin: the proposed admin, the default pubkey cancels a pending proposal
+    ProposeAdmin { new_admin: Pubkey },
+    /// Accept Admin
+    /// makes the proposed admin the admin of the exchange booth
+    ///
+    /// Accounts:
+    /// 1. exchange_booth_account: pda
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 2. new_admin: the signer of the transaction, the proposed admin
+    ///     - is_signer: true,
+    ///     - is_writable: false
+    /// 3. mint A: the mint account of Token A
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 4. mint B: the mint account of Token B
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    AcceptAdmin {},
+    /// Pause
+    /// stops all exchanges of the exchange booth. Deposits and withdrawals
+    /// keep working so the admin can rebalance the vaults
+    ///
+    /// Accounts:
+    /// 1. exchange_booth_account: pda
+    ///     - is_signer: false,
+    ///     - is_writable: true
+    /// 2. authority: the signer of the transaction, admin of the exchange booth
+    ///     - is_signer: true,
+    ///     - is_writable: false
+    /// 3. mint A: the mint account of Token A
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    /// 4. mint B: the mint account of Token B
+    ///     - is_signer: false,
+    ///     - is_writable: false
+    Pause {},
+    /// Unpause
+    /// resumes exchanges of a paused exchange booth
+    ///
+    /// Accounts: same as Pause
+    Unpause {},
+}
+
+impl XBoothIntruction {
+    /// pack serializes the instruction prefixed by the INSTRUCTION_VERSION
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = vec![INSTRUCTION_VERSION];
+        self.serialize(&mut data)
+            .expect("serializing into a vector does not fail");
+        data
+    }
+
+    /// unpack deserializes instruction data created by pack and
+    /// rejects instructions of any other version
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (version, data) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        if *version != INSTRUCTION_VERSION {
+            msg!(
+                "unsupported instruction version {}, expected {}",
+                version,
+                INSTRUCTION_VERSION
+            );
+            return Err(XBoothError::UnsupportedInstructionVersion.into());
+        }
+
+        Self::try_from_slice(data).map_err(|err| {
+            msg!("invalid instruction data. cause {:}", err);
+            ProgramError::InvalidInstructionData
+        })
+    }
+}
+
+/// initialize_exchange_booth creates the InitializeExhangeBooth instruction for
+/// the exchange booth of the payer. mint_a has to be the mint with the smaller
+/// pubkey, the exchange rate is A/B
+pub fn initialize_exchange_booth(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    rate_numerator: u64,
+    rate_denominator: u64,
+    fee_bps: u16,
+) -> Instruction {
+    let (xbooth_pda, _xbooth_bump_seed) = Pubkey::find_program_address(
+        &[b"xbooth", payer.as_ref(), mint_a.as_ref(), mint_b.as_ref()],
+        program_id,
+    );
+    let (vault_a_pda, _vault_a_bump) = find_vault_address(program_id, payer, mint_a, &xbooth_pda);
+    let (vault_b_pda, _vault_b_bump) = find_vault_address(program_id, payer, mint_b, &xbooth_pda);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(xbooth_pda, false),
+            AccountMeta::new_readonly(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(vault_a_pda, false),
+            AccountMeta::new(vault_b_pda, false),
+            AccountMeta::new_readonly(*mint_a, false),
+            AccountMeta::new_readonly(*mint_b, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+        ],
+        data: XBoothIntruction::InitializeExhangeBooth {
+            rate_numerator,
+            rate_denominator,
+            fee_bps,
+        }
+        .pack(),
+    }
+}
+
+/// deposit creates the Deposit instruction moving amount from the token
+/// account of the admin into the vault of mint. other_mint is the second
+/// mint of the exchange booth. The addresses are derived from seed_admin,
+/// the admin that created the exchange booth, and authority is the current
+/// admin signing the instruction
+pub fn deposit(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    authority: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    other_mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (xbooth_pda, _xbooth_bump_seed, mint_a, mint_b) =
+        find_exchange_booth_address(program_id, seed_admin, mint, other_mint);
+    let (vault_pda, _vault_bump) = find_vault_address(program_id, seed_admin, mint, &xbooth_pda);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(xbooth_pda, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*token_account, false),
+            AccountMeta::new(vault_pda, false),
+            AccountMeta::new_readonly(mint_a, false),
+            AccountMeta::new_readonly(mint_b, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: XBoothIntruction::Deposit { amount }.pack(),
+    }
+}
+
+/// withdraw creates the Withdraw instruction moving amount from the vault of
+/// mint into the token account. other_mint is the second mint of the exchange
+/// booth. The token account has to sign the transaction as well. seed_admin
+/// and authority are the same as for deposit
+pub fn withdraw(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    authority: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    other_mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (xbooth_pda, _xbooth_bump_seed, mint_a, mint_b) =
+        find_exchange_booth_address(program_id, seed_admin, mint, other_mint);
+    let (vault_pda, _vault_bump) = find_vault_address(program_id, seed_admin, mint, &xbooth_pda);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(xbooth_pda, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*token_account, false),
+            AccountMeta::new(vault_pda, false),
+            AccountMeta::new_readonly(mint_a, false),
+            AccountMeta::new_readonly(mint_b, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: XBoothIntruction::Withdraw { amount }.pack(),
+    }
+}
+
+/// exchange creates the Exchange instruction for the exchange booth created
+/// by seed_admin for the pair mint_x/mint_y, given in any order. The direction follows
+/// from the mints of the token accounts. Booths priced by an oracle expect the
+/// oracle account to be appended to the accounts
+#[allow(clippy::too_many_arguments)]
+pub fn exchange(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    trader: &Pubkey,
+    from_token_account: &Pubkey,
+    receiving_token_account: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+    amount: u64,
+    min_amount_out: u64,
+    expiry_slot: Option<u64>,
+) -> Instruction {
+    let (xbooth_pda, _xbooth_bump_seed, mint_a, mint_b) =
+        find_exchange_booth_address(program_id, seed_admin, mint_x, mint_y);
+    let (vault_a_pda, _vault_a_bump) =
+        find_vault_address(program_id, seed_admin, &mint_a, &xbooth_pda);
+    let (vault_b_pda, _vault_b_bump) =
+        find_vault_address(program_id, seed_admin, &mint_b, &xbooth_pda);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(xbooth_pda, false),
+            AccountMeta::new_readonly(*trader, true),
+            AccountMeta::new(*from_token_account, false),
+            AccountMeta::new(*receiving_token_account, false),
+            AccountMeta::new(vault_a_pda, false),
+            AccountMeta::new(vault_b_pda, false),
+            AccountMeta::new_readonly(mint_a, false),
+            AccountMeta::new_readonly(mint_b, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: XBoothIntruction::Exchange {
+            amount,
+            min_amount_out,
+            expiry_slot,
+        }
+        .pack(),
+    }
+}
+
+/// exchange_exact_out creates the ExchangeExactOut instruction, the accounts are
+/// the same as for exchange
+#[allow(clippy::too_many_arguments)]
+pub fn exchange_exact_out(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    trader: &Pubkey,
+    from_token_account: &Pubkey,
+    receiving_token_account: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+    amount_out: u64,
+    max_amount_in: u64,
+    expiry_slot: Option<u64>,
+) -> Instruction {
+    let mut instruction = exchange(
+        program_id,
+        seed_admin,
+        trader,
+        from_token_account,
+        receiving_token_account,
+        mint_x,
+        mint_y,
+        0,
+        0,
+        None,
+    );
+    instruction.data = XBoothIntruction::ExchangeExactOut {
+        amount_out,
+        max_amount_in,
+        expiry_slot,
+    }
+    .pack();
+    instruction
+}
+
+/// close_exchange_booth creates the CloseExchangeBooth instruction. mint_a has
+/// to be the mint with the smaller pubkey, the remaining tokens are sent to
+/// token_account_a and token_account_b. seed_admin and authority are the same
+/// as for deposit
+pub fn close_exchange_booth(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    authority: &Pubkey,
+    token_account_a: &Pubkey,
+    token_account_b: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+) -> Instruction {
+    vaults_instruction(
+        program_id,
+        seed_admin,
+        authority,
+        token_account_a,
+        token_account_b,
+        mint_a,
+        mint_b,
+        XBoothIntruction::CloseExchangeBooth {},
+    )
+}
+
+/// withdraw_fees creates the WithdrawFees instruction. mint_a has to be the
+/// mint with the smaller pubkey, the fees are sent to token_account_a and
+/// token_account_b. seed_admin and authority are the same as for deposit
+pub fn withdraw_fees(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    authority: &Pubkey,
+    token_account_a: &Pubkey,
+    token_account_b: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+) -> Instruction {
+    let mut instruction = vaults_instruction(
+        program_id,
+        seed_admin,
+        authority,
+        token_account_a,
+        token_account_b,
+        mint_a,
+        mint_b,
+        XBoothIntruction::WithdrawFees {},
+    );
+    // only closing the exchange booth returns rent to the admin
+    instruction.accounts[1].is_writable = false;
+    instruction
+}
+
+/// vaults_instruction creates an admin instruction moving tokens out of
+/// both vaults, see CloseExchangeBooth and WithdrawFees
+#[allow(clippy::too_many_arguments)]
+fn vaults_instruction(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    authority: &Pubkey,
+    token_account_a: &Pubkey,
+    token_account_b: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    instruction: XBoothIntruction,
+) -> Instruction {
+    let (xbooth_pda, _xbooth_bump_seed) = Pubkey::find_program_address(
+        &[
+            b"xbooth",
+            seed_admin.as_ref(),
+            mint_a.as_ref(),
+            mint_b.as_ref(),
+        ],
+        program_id,
+    );
+    let (vault_a_pda, _vault_a_bump) =
+        find_vault_address(program_id, seed_admin, mint_a, &xbooth_pda);
+    let (vault_b_pda, _vault_b_bump) =
+        find_vault_address(program_id, seed_admin, mint_b, &xbooth_pda);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(xbooth_pda, false),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(vault_a_pda, false),
+            AccountMeta::new(vault_b_pda, false),
+            AccountMeta::new(*token_account_a, false),
+            AccountMeta::new(*token_account_b, false),
+            AccountMeta::new_readonly(*mint_a, false),
+            AccountMeta::new_readonly(*mint_b, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: instruction.pack(),
+    }
+}
+
+/// set_exchange_rate creates the SetExchangeRate instruction, the rate is A/B
+pub fn set_exchange_rate(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    authority: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+    rate_numerator: u64,
+    rate_denominator: u64,
+) -> Instruction {
+    admin_instruction(
+        program_id,
+        seed_admin,
+        authority,
+        mint_x,
+        mint_y,
+        XBoothIntruction::SetExchangeRate {
+            rate_numerator,
+            rate_denominator,
+        },
+    )
+}
+
+/// update_oracle_price creates the UpdateOraclePrice instruction for the
+/// oracle of the admin for the pair mint_x/mint_y
+pub fn update_oracle_price(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+    price: u64,
+    expo: i32,
+    conf: u64,
+) -> Instruction {
+    let (_xbooth_pda, _xbooth_bump_seed, mint_a, mint_b) =
+        find_exchange_booth_address(program_id, admin, mint_x, mint_y);
+    let (oracle_pda, _oracle_bump) = find_oracle_address(program_id, admin, &mint_a, &mint_b);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(oracle_pda, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(mint_a, false),
+            AccountMeta::new_readonly(mint_b, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: XBoothIntruction::UpdateOraclePrice { price, expo, conf }.pack(),
+    }
+}
+
+/// set_oracle creates the SetOracle instruction
+#[allow(clippy::too_many_arguments)]
+pub fn set_oracle(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    authority: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+    oracle: &Pubkey,
+    max_staleness: u64,
+    max_confidence_bps: u16,
+) -> Instruction {
+    admin_instruction(
+        program_id,
+        seed_admin,
+        authority,
+        mint_x,
+        mint_y,
+        XBoothIntruction::SetOracle {
+            oracle: *oracle,
+            max_staleness,
+            max_confidence_bps,
+        },
+    )
+}
+
+/// set_fee creates the SetFee instruction
+pub fn set_fee(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    authority: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+    fee_bps: u16,
+) -> Instruction {
+    admin_instruction(
+        program_id,
+        seed_admin,
+        authority,
+        mint_x,
+        mint_y,
+        XBoothIntruction::SetFee { fee_bps },
+    )
+}
+
+/// set_min_reserves creates the SetMinReserves instruction
+pub fn set_min_reserves(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    authority: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+    min_reserve_a: u64,
+    min_reserve_b: u64,
+) -> Instruction {
+    admin_instruction(
+        program_id,
+        seed_admin,
+        authority,
+        mint_x,
+        mint_y,
+        XBoothIntruction::SetMinReserves {
+            min_reserve_a,
+            min_reserve_b,
+        },
+    )
+}
+
+/// migrate_booth creates the MigrateBooth instruction, the rate is A/B.
+/// Booths in the V1 layout cannot be handed over, so admin is the seed admin.
+/// V1 booths were created with the mints in any order, mint_a and mint_b are
+/// passed in the order of the seeds of the booth
+pub fn migrate_booth(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    rate_numerator: u64,
+    rate_denominator: u64,
+    fee_bps: u16,
+) -> Instruction {
+    let (xbooth_pda, _xbooth_bump_seed) = Pubkey::find_program_address(
+        &[b"xbooth", admin.as_ref(), mint_a.as_ref(), mint_b.as_ref()],
+        program_id,
+    );
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(xbooth_pda, false),
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(*mint_a, false),
+            AccountMeta::new_readonly(*mint_b, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: XBoothIntruction::MigrateBooth {
+            rate_numerator,
+            rate_denominator,
+            fee_bps,
+        }
+        .pack(),
+    }
+}
+
+/// propose_admin creates the ProposeAdmin instruction
+pub fn propose_admin(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    authority: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+    new_admin: &Pubkey,
+) -> Instruction {
+    admin_instruction(
+        program_id,
+        seed_admin,
+        authority,
+        mint_x,
+        mint_y,
+        XBoothIntruction::ProposeAdmin {
+            new_admin: *new_admin,
+        },
+    )
+}
+
+/// accept_admin creates the AcceptAdmin instruction for the exchange booth
+/// created by seed_admin
+pub fn accept_admin(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    new_admin: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+) -> Instruction {
+    admin_instruction(
+        program_id,
+        seed_admin,
+        new_admin,
+        mint_x,
+        mint_y,
+        XBoothIntruction::AcceptAdmin {},
+    )
+}
+
+/// pause creates the Pause instruction
+pub fn pause(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    authority: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+) -> Instruction {
+    admin_instruction(
+        program_id,
+        seed_admin,
+        authority,
+        mint_x,
+        mint_y,
+        XBoothIntruction::Pause {},
+    )
+}
+
+/// unpause creates the Unpause instruction
+pub fn unpause(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    authority: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+) -> Instruction {
+    admin_instruction(
+        program_id,
+        seed_admin,
+        authority,
+        mint_x,
+        mint_y,
+        XBoothIntruction::Unpause {},
+    )
+}
+
+/// admin_instruction creates an instruction that only updates the state of
+/// the exchange booth created by seed_admin for the pair mint_x/mint_y,
+/// signed by authority, the current admin
+fn admin_instruction(
+    program_id: &Pubkey,
+    seed_admin: &Pubkey,
+    authority: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+    instruction: XBoothIntruction,
+) -> Instruction {
+    let (xbooth_pda, _xbooth_bump_seed, mint_a, mint_b) =
+        find_exchange_booth_address(program_id, seed_admin, mint_x, mint_y);
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(xbooth_pda, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(mint_a, false),
+            AccountMeta::new_readonly(mint_b, false),
+        ],
+        data: instruction.pack(),
+    }
 }
@@ -1,5 +1,23 @@
+/// verbose_msg! logs like msg! when the `verbose-logs` feature is enabled.
+/// Without the feature the message is still type checked but compiled away,
+/// messages on error paths keep using msg!
+macro_rules! verbose_msg {
+    ($($arg:tt)+) => {
+        if cfg!(feature = "verbose-logs") {
+            solana_program::msg!($($arg)+)
+        }
+    };
+}
+
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(not(feature = "no-entrypoint"))]
 pub mod entrypoint;
 pub mod errors;
+pub mod events;
 pub mod instruction;
 pub mod processor;
+pub mod quote;
 pub mod state;
@@ -1,21 +1,27 @@
 use crate::errors::XBoothError;
 use crate::instruction::XBoothIntruction;
-use borsh::BorshDeserialize;
 
 use solana_program::{
-    account_info::AccountInfo,
-    decode_error::DecodeError,
-    entrypoint::ProgramResult,
-    msg,
-    program_error::{PrintProgramError, ProgramError},
-    pubkey::Pubkey,
+    account_info::AccountInfo, decode_error::DecodeError, entrypoint::ProgramResult, msg,
+    program_error::PrintProgramError, pubkey::Pubkey,
 };
 
+pub mod accept_admin;
+pub mod close_exchange_booth;
 pub mod deposit;
 pub mod exchange;
 pub mod initialize_exchange_booth;
+pub mod migrate_booth;
+pub mod propose_admin;
+pub mod set_exchange_rate;
+pub mod set_fee;
+pub mod set_min_reserves;
+pub mod set_oracle;
+pub mod set_paused;
+pub mod update_oracle_price;
 pub mod utils;
 pub mod withdraw;
+pub mod withdraw_fees;
 pub struct Processor;
 
 impl Processor {
@@ -24,28 +30,137 @@ impl Processor {
         accounts: &[AccountInfo],
         instruction_data: &[u8],
     ) -> ProgramResult {
-        msg!("xbooth: process instructions");
-        let instruction = XBoothIntruction::try_from_slice(instruction_data).map_err(|err| {
-            msg!("invalid instruction data. cause {:}", err);
-            ProgramError::InvalidInstructionData
-        })?;
-        msg!("instruction: {:?}", instruction);
+        verbose_msg!("xbooth: process instructions");
+        let instruction = XBoothIntruction::unpack(instruction_data)?;
+        verbose_msg!("instruction: {:?}", instruction);
         match instruction {
-            XBoothIntruction::InitializeExhangeBooth {} => {
-                msg!("Initialize Exchange booth");
-                initialize_exchange_booth::process(&program_id, &accounts)?;
+            XBoothIntruction::InitializeExhangeBooth {
+                rate_numerator,
+                rate_denominator,
+                fee_bps,
+            } => {
+                verbose_msg!("Initialize Exchange booth");
+                initialize_exchange_booth::process(
+                    program_id,
+                    accounts,
+                    rate_numerator,
+                    rate_denominator,
+                    fee_bps,
+                )?;
             }
             XBoothIntruction::Deposit { amount } => {
-                msg!("xbooth deposit ");
+                verbose_msg!("xbooth deposit ");
                 deposit::process(program_id, accounts, amount)?;
             }
             XBoothIntruction::Withdraw { amount } => {
-                msg!("xbooth withdraw");
+                verbose_msg!("xbooth withdraw");
                 withdraw::process(program_id, accounts, amount)?;
             }
-            XBoothIntruction::Exchange { amount } => {
-                msg!("xbooth exchange");
-                exchange::process(program_id, accounts, amount)?;
+            XBoothIntruction::Exchange {
+                amount,
+                min_amount_out,
+                expiry_slot,
+            } => {
+                verbose_msg!("xbooth exchange");
+                exchange::process(
+                    program_id,
+                    accounts,
+                    exchange::ExchangeMode::ExactIn {
+                        amount_in: amount,
+                        min_amount_out,
+                    },
+                    expiry_slot,
+                )?;
+            }
+            XBoothIntruction::CloseExchangeBooth {} => {
+                verbose_msg!("xbooth close exchange booth");
+                close_exchange_booth::process(program_id, accounts)?;
+            }
+            XBoothIntruction::SetExchangeRate {
+                rate_numerator,
+                rate_denominator,
+            } => {
+                verbose_msg!("xbooth set exchange rate");
+                set_exchange_rate::process(program_id, accounts, rate_numerator, rate_denominator)?;
+            }
+            XBoothIntruction::UpdateOraclePrice { price, expo, conf } => {
+                verbose_msg!("xbooth update oracle price");
+                update_oracle_price::process(program_id, accounts, price, expo, conf)?;
+            }
+            XBoothIntruction::SetOracle {
+                oracle,
+                max_staleness,
+                max_confidence_bps,
+            } => {
+                verbose_msg!("xbooth set oracle");
+                set_oracle::process(
+                    program_id,
+                    accounts,
+                    oracle,
+                    max_staleness,
+                    max_confidence_bps,
+                )?;
+            }
+            XBoothIntruction::SetFee { fee_bps } => {
+                verbose_msg!("xbooth set fee");
+                set_fee::process(program_id, accounts, fee_bps)?;
+            }
+            XBoothIntruction::WithdrawFees {} => {
+                verbose_msg!("xbooth withdraw fees");
+                withdraw_fees::process(program_id, accounts)?;
+            }
+            XBoothIntruction::ExchangeExactOut {
+                amount_out,
+                max_amount_in,
+                expiry_slot,
+            } => {
+                verbose_msg!("xbooth exchange exact out");
+                exchange::process(
+                    program_id,
+                    accounts,
+                    exchange::ExchangeMode::ExactOut {
+                        amount_out,
+                        max_amount_in,
+                    },
+                    expiry_slot,
+                )?;
+            }
+            XBoothIntruction::SetMinReserves {
+                min_reserve_a,
+                min_reserve_b,
+            } => {
+                verbose_msg!("xbooth set min reserves");
+                set_min_reserves::process(program_id, accounts, min_reserve_a, min_reserve_b)?;
+            }
+            XBoothIntruction::MigrateBooth {
+                rate_numerator,
+                rate_denominator,
+                fee_bps,
+            } => {
+                verbose_msg!("xbooth migrate booth");
+                migrate_booth::process(
+                    program_id,
+                    accounts,
+                    rate_numerator,
+                    rate_denominator,
+                    fee_bps,
+                )?;
+            }
+            XBoothIntruction::ProposeAdmin { new_admin } => {
+                verbose_msg!("xbooth propose admin");
+                propose_admin::process(program_id, accounts, new_admin)?;
+            }
+            XBoothIntruction::AcceptAdmin {} => {
+                verbose_msg!("xbooth accept admin");
+                accept_admin::process(program_id, accounts)?;
+            }
+            XBoothIntruction::Pause {} => {
+                verbose_msg!("xbooth pause");
+                set_paused::process(program_id, accounts, true)?;
+            }
+            XBoothIntruction::Unpause {} => {
+                verbose_msg!("xbooth unpause");
+                set_paused::process(program_id, accounts, false)?;
             }
         }
         Ok(())
@@ -0,0 +1,56 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::next_account_info, account_info::AccountInfo, entrypoint::ProgramResult, msg,
+    pubkey::Pubkey,
+};
+
+use crate::errors::XBoothError;
+use crate::processor::utils;
+
+/// process will hand the exchange booth over to the proposed admin
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let exchange_booth_account = next_account_info(accounts_iter)?;
+    let new_admin_account = next_account_info(accounts_iter)?;
+    let mint_a = next_account_info(accounts_iter)?;
+    let mint_b = next_account_info(accounts_iter)?;
+
+    // * Checks
+    if !new_admin_account.is_signer {
+        msg!("new admin is not a signer!");
+        return Err(XBoothError::AccountIsNotSigner.into());
+    }
+
+    if !exchange_booth_account.is_writable {
+        msg!("Exchange booth needs to be writable");
+        return Err(XBoothError::ExchangeBoothNotWritable.into());
+    }
+
+    let mut xbooth_data = utils::load_exchange_booth(program_id, exchange_booth_account)?;
+    utils::check_exchange_booth_pda(
+        program_id,
+        exchange_booth_account,
+        &xbooth_data,
+        mint_a,
+        mint_b,
+    )?;
+
+    if xbooth_data.pending_admin == Pubkey::default()
+        || xbooth_data.pending_admin != *new_admin_account.key
+    {
+        msg!("{} is not the proposed admin", new_admin_account.key);
+        return Err(XBoothError::InvalidOwner.into());
+    }
+
+    // * Hand over the exchange booth
+    verbose_msg!(
+        "change admin from {} to {}",
+        xbooth_data.admin,
+        new_admin_account.key
+    );
+    xbooth_data.admin = *new_admin_account.key;
+    xbooth_data.pending_admin = Pubkey::default();
+    xbooth_data.serialize(&mut &mut exchange_booth_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+use solana_program::{
+    account_info::next_account_info, account_info::AccountInfo, entrypoint::ProgramResult, msg,
+    program::invoke_signed, pubkey::Pubkey,
+};
+
+use crate::errors::XBoothError;
+use crate::events::XBoothEvent;
+use crate::processor::utils;
+
+/// process will drain both vaults into the token accounts given by the admin,
+/// close the vaults and return the rent of the exchange booth to the admin
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let exchange_booth_account = next_account_info(accounts_iter)?;
+    let authority_account = next_account_info(accounts_iter)?;
+    let vault_a = next_account_info(accounts_iter)?;
+    let vault_b = next_account_info(accounts_iter)?;
+    let token_account_a = next_account_info(accounts_iter)?;
+    let token_account_b = next_account_info(accounts_iter)?;
+    let mint_a = next_account_info(accounts_iter)?;
+    let mint_b = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+
+    // * Checks
+    if !authority_account.is_signer {
+        msg!("authority is not a signer!");
+        return Err(XBoothError::AccountIsNotSigner.into());
+    }
+
+    if !authority_account.is_writable {
+        msg!("authority needs to be writable to receive the rent");
+        return Err(XBoothError::AccountIsNotWritable.into());
+    }
+
+    if !exchange_booth_account.is_writable {
+        msg!("Exchange booth needs to be writable");
+        return Err(XBoothError::ExchangeBoothNotWritable.into());
+    }
+
+    if !vault_a.is_writable || !vault_b.is_writable {
+        msg!("vaults need to be writable");
+        return Err(XBoothError::AccountIsNotWritable.into());
+    }
+
+    if !token_account_a.is_writable || !token_account_b.is_writable {
+        msg!("receiving token accounts must be writable");
+        return Err(XBoothError::AccountIsNotWritable.into());
+    }
+
+    let xbooth_data = utils::load_exchange_booth(program_id, exchange_booth_account)?;
+    utils::check_stored_owner(&xbooth_data, authority_account)?;
+    utils::check_exchange_booth_pda(
+        program_id,
+        exchange_booth_account,
+        &xbooth_data,
+        mint_a,
+        mint_b,
+    )?;
+    let vault_a_data = utils::check_vault(
+        exchange_booth_account,
+        vault_a,
+        &xbooth_data.vault_a,
+        mint_a,
+    )?;
+    let vault_b_data = utils::check_vault(
+        exchange_booth_account,
+        vault_b,
+        &xbooth_data.vault_b,
+        mint_b,
+    )?;
+
+    let xbooth_seeds: &[&[u8]] = &[
+        b"xbooth",
+        xbooth_data.seed_admin.as_ref(),
+        mint_a.key.as_ref(),
+        mint_b.key.as_ref(),
+        &[xbooth_data.booth_bump],
+    ];
+
+    // * Drain and close the vaults
+    let (amount_a, amount_b) = (vault_a_data.amount, vault_b_data.amount);
+    for (vault, vault_data, token_account) in [
+        (vault_a, vault_a_data, token_account_a),
+        (vault_b, vault_b_data, token_account_b),
+    ] {
+        if vault_data.amount > 0 {
+            verbose_msg!(
+                "transfer remaining {} from vault {} to {}",
+                vault_data.amount,
+                vault.key,
+                token_account.key
+            );
+            invoke_signed(
+                &spl_token::instruction::transfer(
+                    token_program.key,
+                    vault.key,
+                    token_account.key,
+                    exchange_booth_account.key,
+                    &[],
+                    vault_data.amount,
+                )?,
+                &[
+                    token_program.clone(),
+                    vault.clone(),
+                    token_account.clone(),
+                    exchange_booth_account.clone(),
+                ],
+                &[xbooth_seeds],
+            )?;
+        }
+
+        verbose_msg!("close vault {}", vault.key);
+        invoke_signed(
+            &spl_token::instruction::close_account(
+                token_program.key,
+                vault.key,
+                authority_account.key,
+                exchange_booth_account.key,
+                &[],
+            )?,
+            &[
+                token_program.clone(),
+                vault.clone(),
+                authority_account.clone(),
+                exchange_booth_account.clone(),
+            ],
+            &[xbooth_seeds],
+        )?;
+    }
+
+    // * Return the rent of the exchange booth to the admin
+    let booth_lamports = exchange_booth_account.lamports();
+    **authority_account.lamports.borrow_mut() = authority_account
+        .lamports()
+        .checked_add(booth_lamports)
+        .ok_or(XBoothError::AmountOverflow)?;
+    **exchange_booth_account.lamports.borrow_mut() = 0;
+    exchange_booth_account.data.borrow_mut().fill(0);
+
+    XBoothEvent::Closed {
+        booth: *exchange_booth_account.key,
+        admin: *authority_account.key,
+        amount_a,
+        amount_b,
+    }
+    .emit()
+}
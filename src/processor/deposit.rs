@@ -1,20 +1,14 @@
-use borsh::BorshDeserialize;
 use solana_program::{
-    account_info::next_account_info,
-    account_info::AccountInfo,
-    entrypoint::ProgramResult,
-    msg,
-    program::invoke,
-    program_pack::{IsInitialized, Pack},
-    pubkey::Pubkey,
+    account_info::next_account_info, account_info::AccountInfo, entrypoint::ProgramResult, msg,
+    program::invoke, program_pack::Pack, pubkey::Pubkey,
 };
-use spl_token::{instruction, state::Account};
+use spl_token::instruction;
 
 use crate::errors::XBoothError;
+use crate::events::XBoothEvent;
 use crate::processor::utils;
-use crate::state::ExchangeBoothAccount;
 
-pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: f64) -> ProgramResult {
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
 
     let exchange_booth_account = next_account_info(accounts_iter)?;
@@ -49,63 +43,41 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: f64) -> Pr
     let token_account_data =
         spl_token::state::Account::unpack_from_slice(&token_account.try_borrow_data()?)?;
 
-    let is_transfer_a_token = token_account_data.mint == *mint_a.key;
-
-    let vault_account = Account::unpack(&vault.data.borrow())
-        .map_err(|err| {
-            msg!("invalid vault account");
-            return err;
-        })
-        .unwrap();
+    // check the owner of the exchange booth account
+    let xbooth_data = utils::load_exchange_booth(program_id, exchange_booth_account)?;
+    utils::check_stored_owner(&xbooth_data, authority)?;
+    utils::check_exchange_booth_pda(
+        program_id,
+        exchange_booth_account,
+        &xbooth_data,
+        mint_a,
+        mint_b,
+    )?;
 
-    if !vault_account.is_initialized() {
-        msg!("vault is not initialized");
-        return Err(XBoothError::AccountNotInitialized.into());
-    }
+    // Decide if token A or B
+    let (stored_vault, vault_mint) = if *vault.key == xbooth_data.vault_a {
+        (&xbooth_data.vault_a, mint_a)
+    } else if *vault.key == xbooth_data.vault_b {
+        (&xbooth_data.vault_b, mint_b)
+    } else {
+        msg!("vault is not a vault of the exchange booth");
+        return Err(XBoothError::InvalidVaultAccount.into());
+    };
+
+    let vault_account =
+        utils::check_vault(exchange_booth_account, vault, stored_vault, vault_mint)?;
 
     if vault_account.mint != token_account_data.mint {
         msg!("vault account and token account is of different mints");
         return Err(XBoothError::InvalidMint.into());
     }
 
-    // Decide if token A or B
-    // check the owner of the exchange booth account
-    let exchange_booth_data = &mut (*exchange_booth_account.data).borrow_mut();
-    let xbooth_data = ExchangeBoothAccount::try_from_slice(&exchange_booth_data).unwrap();
-    if xbooth_data.admin != *authority.key {
-        msg!("owner is not admin for the exchange booth");
-        return Err(XBoothError::InvalidOwner.into());
-    }
-
-    // Check the vault
-    let (_vault_pda, _vault_bump_seed) = utils::get_vault_pda(
-        program_id,
-        exchange_booth_account,
-        authority,
-        if is_transfer_a_token { mint_a } else { mint_b },
-        vault,
-    )
-    .unwrap();
-
-    let (_xbooth_pda, _xbooth_bump) = utils::get_exchange_booth_pda(
-        program_id,
-        exchange_booth_account,
-        authority,
-        mint_a,
-        mint_b,
-    )
-    .unwrap();
-
     // check if enough funds in owner account
-    let amount_lamports =
-        utils::amount_to_lamports(if is_transfer_a_token { mint_a } else { mint_b }, amount)
-            .unwrap();
-
-    if token_account_data.amount < amount_lamports {
+    if token_account_data.amount < amount {
         msg!("not enough funds in account to transfer");
         return Err(XBoothError::InsufficientFunds.into());
     }
-    msg!("lamports in token_account: {}", token_account_data.amount);
+    verbose_msg!("lamports in token_account: {}", token_account_data.amount);
 
     // Transfer amount from owner to the vault
     let transfer_ix = instruction::transfer(
@@ -114,9 +86,8 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: f64) -> Pr
         &vault.key,
         &authority.key,
         &[&authority.key],
-        amount_lamports,
-    )
-    .unwrap();
+        amount,
+    )?;
 
     invoke(
         &transfer_ix,
@@ -126,8 +97,14 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: f64) -> Pr
             vault.clone(),
             authority.clone(),
         ],
-    )
-    .unwrap();
-
-    Ok(())
+    )?;
+
+    XBoothEvent::Deposited {
+        booth: *exchange_booth_account.key,
+        mint: vault_account.mint,
+        vault: *vault.key,
+        token_account: *token_account.key,
+        amount,
+    }
+    .emit()
 }
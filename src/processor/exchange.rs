@@ -1,20 +1,32 @@
+use borsh::BorshSerialize;
 use solana_program::{
     account_info::next_account_info,
     account_info::AccountInfo,
+    clock::Clock,
     entrypoint::ProgramResult,
     msg,
     program::{invoke, invoke_signed},
     program_pack::Pack,
     pubkey::Pubkey,
+    sysvar::Sysvar,
 };
 
 use crate::errors::XBoothError;
+use crate::events::XBoothEvent;
 use crate::processor::utils;
+use crate::quote;
 
-pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: f64) -> ProgramResult {
+pub use crate::quote::ExchangeMode;
+
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mode: ExchangeMode,
+    expiry_slot: Option<u64>,
+) -> ProgramResult {
     let accounts = &mut accounts.iter();
     let exchange_booth_account = next_account_info(accounts)?;
-    let authority_account = next_account_info(accounts)?;
+    let trader_account = next_account_info(accounts)?;
     let from_token_account = next_account_info(accounts)?;
     let receiving_token_account = next_account_info(accounts)?;
     let vault_a = next_account_info(accounts)?;
@@ -24,11 +36,28 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: f64) -> Pr
     let token_program = next_account_info(accounts)?;
 
     // * checks
-    if !authority_account.is_signer {
-        msg!("authority needs to have signer privilege");
+    if let Some(expiry_slot) = expiry_slot {
+        let current_slot = Clock::get()?.slot;
+        if current_slot > expiry_slot {
+            msg!(
+                "exchange expired at slot {}, current slot {}",
+                expiry_slot,
+                current_slot
+            );
+            return Err(XBoothError::TradeExpired.into());
+        }
+    }
+
+    if !trader_account.is_signer {
+        msg!("trader needs to have signer privilege");
         return Err(XBoothError::AccountIsNotSigner.into());
     }
 
+    if !exchange_booth_account.is_writable {
+        msg!("exchange booth account needs to be writable");
+        return Err(XBoothError::ExchangeBoothNotWritable.into());
+    }
+
     if !receiving_token_account.is_writable {
         msg!("receiving token account needs to be writable");
         return Err(XBoothError::AccountIsNotWritable.into());
@@ -54,97 +83,198 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: f64) -> Pr
     let from_token_account_data =
         spl_token::state::Account::unpack(&from_token_account.data.borrow())?;
 
-    if &from_token_account_data.mint != mint_a.key {
-        msg!("sending token account is not of the same mint as token A");
-        return Err(XBoothError::InvalidMint.into());
-    }
-
-    if &receiving_token_account_data.mint != mint_b.key {
-        msg!("receving token account is not of the same mint as token B");
-        return Err(XBoothError::InvalidMint.into());
-    }
-
     if receiving_token_account_data.mint == from_token_account_data.mint {
         msg!("receiving token account cannot be of the same mint as the sending token account");
         return Err(XBoothError::UniqueMintAccounts.into());
     }
 
-    // get exchange_booth_account pda and bump
-    let (_exchange_booth_pda, exchange_booth_bump) = utils::get_exchange_booth_pda(
+    // Decide the direction of the exchange from the mints of the token accounts
+    let is_a_to_b = if from_token_account_data.mint == *mint_a.key
+        && receiving_token_account_data.mint == *mint_b.key
+    {
+        true
+    } else if from_token_account_data.mint == *mint_b.key
+        && receiving_token_account_data.mint == *mint_a.key
+    {
+        false
+    } else {
+        msg!("token accounts must hold token A and token B of the exchange booth");
+        return Err(XBoothError::InvalidMint.into());
+    };
+    let (mint_in, mint_out, vault_in, vault_out) = if is_a_to_b {
+        (mint_a, mint_b, vault_a, vault_b)
+    } else {
+        (mint_b, mint_a, vault_b, vault_a)
+    };
+
+    // the trader does not need to be the admin, the exchange booth pda
+    // is derived from the seed admin stored in the program owned booth account
+    let mut xbooth_data = utils::load_exchange_booth(program_id, exchange_booth_account)?;
+    utils::check_exchange_booth_pda(
         program_id,
         exchange_booth_account,
-        authority_account,
+        &xbooth_data,
         mint_a,
         mint_b,
-    )
-    .unwrap();
+    )?;
+
+    if xbooth_data.is_paused {
+        msg!("exchange booth is paused by the admin");
+        return Err(XBoothError::BoothPaused.into());
+    }
+
+    let vault_a_data = utils::check_vault(
+        exchange_booth_account,
+        vault_a,
+        &xbooth_data.vault_a,
+        mint_a,
+    )?;
+    let vault_b_data = utils::check_vault(
+        exchange_booth_account,
+        vault_b,
+        &xbooth_data.vault_b,
+        mint_b,
+    )?;
 
     // * Exchange
     // send
-    let token_a_b_xr = 0.5;
-    let amount_a: u64 = utils::amount_to_lamports(mint_a, amount).unwrap();
-    let amount_b: u64 = utils::amount_to_lamports(mint_b, amount * token_a_b_xr).unwrap();
+    let (rate_numerator, rate_denominator) = if xbooth_data.oracle == Pubkey::default() {
+        (xbooth_data.rate_numerator, xbooth_data.rate_denominator)
+    } else {
+        let oracle_account = next_account_info(accounts)?;
+        utils::get_oracle_rate(program_id, &xbooth_data, oracle_account)?
+    };
+    let (rate_in_out_numerator, rate_in_out_denominator) =
+        quote::rate_in_out(rate_numerator, rate_denominator, is_a_to_b);
+    let decimals_in = utils::get_mint_decimals(mint_in)?;
+    let decimals_out = utils::get_mint_decimals(mint_out)?;
 
-    msg!(
-        "transfer amount: {} from token account to vault A",
-        amount_a
+    // the fee is charged on the output and stays in the out vault
+    let quote = match quote::quote(
+        mode,
+        rate_in_out_numerator,
+        rate_in_out_denominator,
+        decimals_in,
+        decimals_out,
+        xbooth_data.fee_bps,
+    ) {
+        Ok(quote) => quote,
+        Err(err) => {
+            msg!("cannot quote the exchange: {}", err);
+            return Err(XBoothError::from(err).into());
+        }
+    };
+    if let Err(err) = quote::check_slippage(mode, &quote) {
+        msg!(
+            "exchange of {} for {} is outside the slippage limit of {:?}",
+            quote.amount_in,
+            quote.amount_out,
+            mode
+        );
+        return Err(XBoothError::from(err).into());
+    }
+    let (amount_in, amount_out, fee) = (quote.amount_in, quote.amount_out, quote.fee);
+
+    // * Check the liquidity of the out vault
+    let vault_out_account = if is_a_to_b {
+        vault_b_data
+    } else {
+        vault_a_data
+    };
+    let (fees_out, min_reserve_out) = if is_a_to_b {
+        (xbooth_data.fees_b, xbooth_data.min_reserve_b)
+    } else {
+        (xbooth_data.fees_a, xbooth_data.min_reserve_a)
+    };
+    // the fee is set aside from the available balance as well, so the
+    // exchange cannot eat into the min reserve
+    let available_out =
+        quote::available_amount(vault_out_account.amount, fees_out, min_reserve_out);
+    if quote.gross_amount_out() > available_out {
+        msg!(
+            "vault {} has {} available to exchange, {} requested",
+            vault_out.key,
+            available_out,
+            quote.gross_amount_out()
+        );
+        return Err(XBoothError::InsufficientLiquidity.into());
+    }
+
+    let fees_out = if is_a_to_b {
+        &mut xbooth_data.fees_b
+    } else {
+        &mut xbooth_data.fees_a
+    };
+    *fees_out = fees_out
+        .checked_add(fee)
+        .ok_or(XBoothError::AmountOverflow)?;
+    xbooth_data.serialize(&mut &mut exchange_booth_account.data.borrow_mut()[..])?;
+
+    verbose_msg!(
+        "transfer amount: {} from token account to vault {}",
+        amount_in,
+        vault_in.key
     );
-    let deposit_into_a_ix = spl_token::instruction::transfer(
-        &token_program.key,
-        &from_token_account.key,
-        &vault_a.key,
-        &authority_account.key,
-        &[&authority_account.key],
-        amount_a,
-    )
-    .unwrap();
+    let deposit_ix = spl_token::instruction::transfer(
+        token_program.key,
+        from_token_account.key,
+        vault_in.key,
+        trader_account.key,
+        &[trader_account.key],
+        amount_in,
+    )?;
 
     invoke(
-        &deposit_into_a_ix,
+        &deposit_ix,
         &[
             token_program.clone(),
             from_token_account.clone(),
-            vault_a.clone(),
-            authority_account.clone(),
+            vault_in.clone(),
+            trader_account.clone(),
         ],
-    )
-    .unwrap();
+    )?;
 
     // return
-    let vault_b_account =
-        spl_token::state::Account::unpack_from_slice(&vault_b.try_borrow_mut_data()?)?;
-    msg!(
-        "transfer amount: {} from vault B with balance {} to receiving token account",
-        amount_b,
-        vault_b_account.amount
+    verbose_msg!(
+        "transfer amount: {} from vault {} with balance {} to receiving token account",
+        amount_out,
+        vault_out.key,
+        vault_out_account.amount
     );
-    let withdraw_from_b_ix = spl_token::instruction::transfer(
-        &token_program.key,
-        &vault_b.key,
-        &receiving_token_account.key,
-        &exchange_booth_account.key,
+    let withdraw_ix = spl_token::instruction::transfer(
+        token_program.key,
+        vault_out.key,
+        receiving_token_account.key,
+        exchange_booth_account.key,
         &[],
-        amount_b,
-    )
-    .unwrap();
+        amount_out,
+    )?;
 
     invoke_signed(
-        &withdraw_from_b_ix,
+        &withdraw_ix,
         &[
             token_program.clone(),
-            vault_b.clone(),
+            vault_out.clone(),
             receiving_token_account.clone(),
             exchange_booth_account.clone(),
         ],
         &[&[
             b"xbooth",
-            authority_account.key.as_ref(),
+            xbooth_data.seed_admin.as_ref(),
             mint_a.key.as_ref(),
             mint_b.key.as_ref(),
-            &[exchange_booth_bump],
+            &[xbooth_data.booth_bump],
         ]],
-    )
-    .unwrap();
+    )?;
 
-    Ok(())
+    XBoothEvent::Exchanged {
+        booth: *exchange_booth_account.key,
+        trader: *trader_account.key,
+        mint_in: *mint_in.key,
+        mint_out: *mint_out.key,
+        amount_in,
+        amount_out,
+        fee,
+    }
+    .emit()
 }
@@ -10,15 +10,23 @@ use solana_program::{
     pubkey::Pubkey,
     rent::Rent,
     system_instruction::create_account,
+    system_program,
     sysvar::Sysvar,
 };
 use spl_token::{instruction, state::Account as TokenAccount};
 
 use crate::errors::XBoothError;
+use crate::events::XBoothEvent;
 use crate::processor;
 use crate::state;
 
-pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rate_numerator: u64,
+    rate_denominator: u64,
+    fee_bps: u16,
+) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let exchange_booth_account = next_account_info(accounts_iter)?;
     let payer = next_account_info(accounts_iter)?;
@@ -50,31 +58,76 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
         return Err(XBoothError::AccountIsNotWritable.into());
     }
 
+    processor::utils::check_exchange_rate(rate_numerator, rate_denominator)?;
+    processor::utils::check_fee(fee_bps)?;
+
+    if *token_program.key != spl_token::id() {
+        msg!(
+            "token program {} is not the spl token program",
+            token_program.key
+        );
+        return Err(XBoothError::InvalidTokenProgram.into());
+    }
+
+    if mint_a.key == mint_b.key {
+        msg!("mint A and mint B are both {}", mint_a.key);
+        return Err(XBoothError::IdenticalMints.into());
+    }
+
+    for mint in [mint_a, mint_b] {
+        processor::utils::check_mint(mint)?;
+    }
+
+    // only one exchange booth can exist for a pair of mints of an admin
+    if processor::utils::canonical_mints(mint_a.key, mint_b.key) != (*mint_a.key, *mint_b.key) {
+        msg!(
+            "mint A {} must be ordered before mint B {}",
+            mint_a.key,
+            mint_b.key
+        );
+        return Err(XBoothError::NonCanonicalMintOrder.into());
+    }
+
+    // the exchange booth and the vaults must not hold any state yet
+    for account in [exchange_booth_account, vault_a, vault_b] {
+        if !account.data_is_empty() || *account.owner != system_program::id() {
+            msg!("account {} is already initialized", account.key);
+            return Err(XBoothError::AlreadyInitialized.into());
+        }
+    }
+
     // * --- Vault A
     // find pda
-    let (vault_a_pda, vault_a_bump_seed) =
-        processor::utils::get_vault_pda(program_id, exchange_booth_account, payer, mint_a, vault_a)
-            .unwrap();
+    let (vault_a_pda, vault_a_bump_seed) = processor::utils::get_vault_pda(
+        program_id,
+        exchange_booth_account,
+        payer,
+        mint_a,
+        vault_a,
+    )?;
 
     // * --- Vault B
     // find pda
-    let (vault_b_pda, vault_b_bump_seed) =
-        processor::utils::get_vault_pda(program_id, exchange_booth_account, payer, mint_b, vault_b)
-            .unwrap();
+    let (vault_b_pda, vault_b_bump_seed) = processor::utils::get_vault_pda(
+        program_id,
+        exchange_booth_account,
+        payer,
+        mint_b,
+        vault_b,
+    )?;
 
     // * -- Exchange Booth Account
     // get pda
-    let (xbooth_pda, xbooth_bump_seed) = processor::utils::get_exchange_booth_pda(
+    let (_xbooth_pda, xbooth_bump_seed) = processor::utils::get_exchange_booth_pda(
         program_id,
         exchange_booth_account,
-        payer,
+        payer.key,
         mint_a,
         mint_b,
-    )
-    .unwrap();
+    )?;
 
     // * Create exchange booth account
-    msg!("create exchange booth account");
+    verbose_msg!("create exchange booth account");
     invoke_signed(
         &create_account(
             &payer.key,
@@ -101,7 +154,7 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let rent = Rent::get()?.minimum_balance(TokenAccount::LEN);
 
     // * Create and initialize vault a
-    msg!("create Vault A");
+    verbose_msg!("create Vault A");
     invoke_signed(
         &create_account(
             &payer.key,
@@ -138,7 +191,7 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     )?;
 
     // * Create and initialize vault b
-    msg!("create Vault B");
+    verbose_msg!("create Vault B");
     invoke_signed(
         &create_account(
             &payer.key,
@@ -175,11 +228,43 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
 
     // * Allocate data to exchange booth
     let xbooth_info = state::ExchangeBoothAccount {
+        version: state::EXCHANGE_BOOTH_ACCOUNT_VERSION,
+        is_initialized: true,
         admin: *payer.key,
         vault_a: *vault_a.key,
         vault_b: *vault_b.key,
+        rate_numerator,
+        rate_denominator,
+        oracle: Pubkey::default(),
+        max_oracle_staleness: 0,
+        max_oracle_confidence_bps: 0,
+        fee_bps,
+        fees_a: 0,
+        fees_b: 0,
+        min_reserve_a: 0,
+        min_reserve_b: 0,
+        booth_bump: xbooth_bump_seed,
+        vault_a_bump: vault_a_bump_seed,
+        vault_b_bump: vault_b_bump_seed,
+        seed_admin: *payer.key,
+        pending_admin: Pubkey::default(),
+        is_paused: false,
+        padding: [0; 31],
+        padding_ext: [0; 32],
     };
     let exchange_booth_data = &mut *exchange_booth_account.data.borrow_mut();
     xbooth_info.serialize(exchange_booth_data)?;
-    Ok(())
+
+    XBoothEvent::BoothInitialized {
+        booth: *exchange_booth_account.key,
+        admin: *payer.key,
+        mint_a: *mint_a.key,
+        mint_b: *mint_b.key,
+        vault_a: *vault_a.key,
+        vault_b: *vault_b.key,
+        rate_numerator,
+        rate_denominator,
+        fee_bps,
+    }
+    .emit()
 }
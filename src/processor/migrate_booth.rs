@@ -0,0 +1,156 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::next_account_info, account_info::AccountInfo, entrypoint::ProgramResult, msg,
+    program::invoke, program_error::ProgramError, pubkey::Pubkey, rent::Rent, system_instruction,
+    sysvar::Sysvar,
+};
+
+use crate::errors::XBoothError;
+use crate::processor::utils;
+use crate::state::{
+    ExchangeBoothAccount, ExchangeBoothAccountV1, EXCHANGE_BOOTH_ACCOUNT_LEN,
+    EXCHANGE_BOOTH_ACCOUNT_V1_LEN,
+};
+
+/// process will upgrade an exchange booth created with the V1 layout to the
+/// current layout, reallocating the account and topping up its rent. The
+/// bump seeds that the V1 layout does not store are searched for once here
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rate_numerator: u64,
+    rate_denominator: u64,
+    fee_bps: u16,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let exchange_booth_account = next_account_info(accounts_iter)?;
+    let authority_account = next_account_info(accounts_iter)?;
+    let mint_a = next_account_info(accounts_iter)?;
+    let mint_b = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // * Checks
+    if !authority_account.is_signer {
+        msg!("authority is not a signer!");
+        return Err(XBoothError::AccountIsNotSigner.into());
+    }
+
+    if !authority_account.is_writable {
+        msg!("authority needs to be writable to pay the rent");
+        return Err(XBoothError::AccountIsNotWritable.into());
+    }
+
+    if !exchange_booth_account.is_writable {
+        msg!("Exchange booth needs to be writable");
+        return Err(XBoothError::ExchangeBoothNotWritable.into());
+    }
+
+    if exchange_booth_account.owner != program_id {
+        msg!("exchange booth account is not owned by the program");
+        return Err(XBoothError::InvalidAccountAddress.into());
+    }
+
+    if exchange_booth_account.data_len() != EXCHANGE_BOOTH_ACCOUNT_V1_LEN {
+        msg!("exchange booth does not use the v1 layout");
+        return Err(XBoothError::InvalidAccountVersion.into());
+    }
+
+    utils::check_exchange_rate(rate_numerator, rate_denominator)?;
+    utils::check_fee(fee_bps)?;
+
+    let xbooth_data_v1 =
+        ExchangeBoothAccountV1::try_from_slice(&exchange_booth_account.try_borrow_data()?)
+            .map_err(|err| {
+                msg!("exchange booth account is not initialized. cause {:}", err);
+                XBoothError::AccountNotInitialized
+            })?;
+    if authority_account.key != &xbooth_data_v1.admin {
+        msg!("authority is not stored as sole owner of the exchange_booth_account");
+        return Err(XBoothError::InvalidOwner.into());
+    }
+
+    // * Find the bump seeds of the booth and its vaults
+    let (_xbooth_pda, booth_bump) = utils::get_exchange_booth_pda(
+        program_id,
+        exchange_booth_account,
+        authority_account.key,
+        mint_a,
+        mint_b,
+    )?;
+    let vault_a_bump = find_vault_bump(
+        program_id,
+        exchange_booth_account,
+        authority_account,
+        mint_a,
+        &xbooth_data_v1.vault_a,
+    )?;
+    let vault_b_bump = find_vault_bump(
+        program_id,
+        exchange_booth_account,
+        authority_account,
+        mint_b,
+        &xbooth_data_v1.vault_b,
+    )?;
+
+    let xbooth_data = ExchangeBoothAccount::from_v1(
+        xbooth_data_v1,
+        rate_numerator,
+        rate_denominator,
+        fee_bps,
+        booth_bump,
+        vault_a_bump,
+        vault_b_bump,
+    );
+
+    // * Top up the rent for the new layout
+    let required_lamports = Rent::get()?.minimum_balance(EXCHANGE_BOOTH_ACCOUNT_LEN);
+    let missing_lamports = required_lamports.saturating_sub(exchange_booth_account.lamports());
+    if missing_lamports > 0 {
+        verbose_msg!("top up rent of exchange booth with {}", missing_lamports);
+        invoke(
+            &system_instruction::transfer(
+                authority_account.key,
+                exchange_booth_account.key,
+                missing_lamports,
+            ),
+            &[
+                authority_account.clone(),
+                exchange_booth_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+
+    // * Write the booth in the new layout
+    verbose_msg!(
+        "migrate exchange booth from {} to {} bytes",
+        EXCHANGE_BOOTH_ACCOUNT_V1_LEN,
+        EXCHANGE_BOOTH_ACCOUNT_LEN
+    );
+    exchange_booth_account.realloc(EXCHANGE_BOOTH_ACCOUNT_LEN, false)?;
+    xbooth_data.serialize(&mut &mut exchange_booth_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
+
+/// find_vault_bump returns the bump seed of the vault of mint, which has to
+/// be the vault stored in the V1 layout
+fn find_vault_bump(
+    program_id: &Pubkey,
+    exchange_booth_account: &AccountInfo,
+    admin: &AccountInfo,
+    mint: &AccountInfo,
+    stored_vault: &Pubkey,
+) -> Result<u8, ProgramError> {
+    let (vault_pda, vault_bump) =
+        utils::find_vault_address(program_id, admin.key, mint.key, exchange_booth_account.key);
+    if vault_pda != *stored_vault {
+        msg!(
+            "vault {} is not the vault of mint {}",
+            stored_vault,
+            mint.key
+        );
+        return Err(XBoothError::InvalidVaultAccount.into());
+    }
+    Ok(vault_bump)
+}
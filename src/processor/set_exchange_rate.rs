@@ -0,0 +1,64 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::next_account_info, account_info::AccountInfo, entrypoint::ProgramResult, msg,
+    pubkey::Pubkey,
+};
+
+use crate::errors::XBoothError;
+use crate::events::XBoothEvent;
+use crate::processor::utils;
+
+/// process will update the exchange rate A/B stored in the exchange booth
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rate_numerator: u64,
+    rate_denominator: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let exchange_booth_account = next_account_info(accounts_iter)?;
+    let authority_account = next_account_info(accounts_iter)?;
+    let mint_a = next_account_info(accounts_iter)?;
+    let mint_b = next_account_info(accounts_iter)?;
+
+    // * Checks
+    if !authority_account.is_signer {
+        msg!("authority is not a signer!");
+        return Err(XBoothError::AccountIsNotSigner.into());
+    }
+
+    if !exchange_booth_account.is_writable {
+        msg!("Exchange booth needs to be writable");
+        return Err(XBoothError::ExchangeBoothNotWritable.into());
+    }
+
+    let mut xbooth_data = utils::load_exchange_booth(program_id, exchange_booth_account)?;
+    utils::check_stored_owner(&xbooth_data, authority_account)?;
+    utils::check_exchange_booth_pda(
+        program_id,
+        exchange_booth_account,
+        &xbooth_data,
+        mint_a,
+        mint_b,
+    )?;
+    utils::check_exchange_rate(rate_numerator, rate_denominator)?;
+
+    // * Update the rate
+    verbose_msg!(
+        "change exchange rate from {}/{} to {}/{}",
+        xbooth_data.rate_numerator,
+        xbooth_data.rate_denominator,
+        rate_numerator,
+        rate_denominator
+    );
+    xbooth_data.rate_numerator = rate_numerator;
+    xbooth_data.rate_denominator = rate_denominator;
+    xbooth_data.serialize(&mut &mut exchange_booth_account.data.borrow_mut()[..])?;
+
+    XBoothEvent::RateChanged {
+        booth: *exchange_booth_account.key,
+        rate_numerator,
+        rate_denominator,
+    }
+    .emit()
+}
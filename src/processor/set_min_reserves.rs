@@ -0,0 +1,55 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::next_account_info, account_info::AccountInfo, entrypoint::ProgramResult, msg,
+    pubkey::Pubkey,
+};
+
+use crate::errors::XBoothError;
+use crate::processor::utils;
+
+/// process will update the min balance kept in each vault of the exchange booth
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    min_reserve_a: u64,
+    min_reserve_b: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let exchange_booth_account = next_account_info(accounts_iter)?;
+    let authority_account = next_account_info(accounts_iter)?;
+    let mint_a = next_account_info(accounts_iter)?;
+    let mint_b = next_account_info(accounts_iter)?;
+
+    // * Checks
+    if !authority_account.is_signer {
+        msg!("authority is not a signer!");
+        return Err(XBoothError::AccountIsNotSigner.into());
+    }
+
+    if !exchange_booth_account.is_writable {
+        msg!("Exchange booth needs to be writable");
+        return Err(XBoothError::ExchangeBoothNotWritable.into());
+    }
+
+    let mut xbooth_data = utils::load_exchange_booth(program_id, exchange_booth_account)?;
+    utils::check_stored_owner(&xbooth_data, authority_account)?;
+    utils::check_exchange_booth_pda(
+        program_id,
+        exchange_booth_account,
+        &xbooth_data,
+        mint_a,
+        mint_b,
+    )?;
+
+    // * Update the reserves
+    verbose_msg!(
+        "keep min reserves {} in vault A and {} in vault B",
+        min_reserve_a,
+        min_reserve_b
+    );
+    xbooth_data.min_reserve_a = min_reserve_a;
+    xbooth_data.min_reserve_b = min_reserve_b;
+    xbooth_data.serialize(&mut &mut exchange_booth_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
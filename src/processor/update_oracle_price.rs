@@ -0,0 +1,102 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::next_account_info, account_info::AccountInfo, clock::Clock,
+    entrypoint::ProgramResult, msg, program::invoke_signed, pubkey::Pubkey, rent::Rent,
+    system_instruction::create_account, sysvar::Sysvar,
+};
+
+use crate::errors::XBoothError;
+use crate::processor::utils;
+use crate::state::{OracleAccount, ORACLE_ACCOUNT_LEN};
+
+/// process will publish a new price to the oracle of the pair A/B,
+/// creating the oracle account on the first update
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    price: u64,
+    expo: i32,
+    conf: u64,
+) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let oracle_account = next_account_info(accounts_iter)?;
+    let authority_account = next_account_info(accounts_iter)?;
+    let mint_a = next_account_info(accounts_iter)?;
+    let mint_b = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    // * Checks
+    if !authority_account.is_signer {
+        msg!("authority is not a signer!");
+        return Err(XBoothError::AccountIsNotSigner.into());
+    }
+
+    if !oracle_account.is_writable {
+        msg!("oracle account needs to be writable");
+        return Err(XBoothError::AccountIsNotWritable.into());
+    }
+
+    let (_oracle_pda, oracle_bump) = utils::get_oracle_pda(
+        program_id,
+        oracle_account,
+        authority_account,
+        mint_a,
+        mint_b,
+    )?;
+
+    if oracle_account.data_is_empty() {
+        // * Create the oracle account on the first update
+        verbose_msg!("create oracle account");
+        invoke_signed(
+            &create_account(
+                authority_account.key,
+                oracle_account.key,
+                Rent::get()?.minimum_balance(ORACLE_ACCOUNT_LEN),
+                ORACLE_ACCOUNT_LEN as u64,
+                program_id,
+            ),
+            &[
+                authority_account.clone(),
+                oracle_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[
+                b"xbooth_oracle",
+                authority_account.key.as_ref(),
+                mint_a.key.as_ref(),
+                mint_b.key.as_ref(),
+                &[oracle_bump],
+            ]],
+        )?;
+    } else {
+        if oracle_account.owner != program_id {
+            msg!("oracle account is not owned by the program");
+            return Err(XBoothError::InvalidOracleAccount.into());
+        }
+
+        let oracle_data = OracleAccount::try_from_slice(&oracle_account.data.borrow())?;
+        if oracle_data.admin != *authority_account.key {
+            msg!("authority is not the admin of the oracle");
+            return Err(XBoothError::InvalidOwner.into());
+        }
+    }
+
+    // * Publish the price
+    let oracle_data = OracleAccount {
+        admin: *authority_account.key,
+        price,
+        expo,
+        conf,
+        publish_slot: Clock::get()?.slot,
+    };
+    verbose_msg!(
+        "oracle price {} * 10^{} +- {} at slot {}",
+        oracle_data.price,
+        oracle_data.expo,
+        oracle_data.conf,
+        oracle_data.publish_slot
+    );
+    oracle_data.serialize(&mut &mut oracle_account.data.borrow_mut()[..])?;
+
+    Ok(())
+}
@@ -1,21 +1,95 @@
 use crate::errors::XBoothError;
-use crate::state::ExchangeBoothAccount;
+use crate::state::{
+    ExchangeBoothAccount, OracleAccount, EXCHANGE_BOOTH_ACCOUNT_V1_LEN,
+    EXCHANGE_BOOTH_ACCOUNT_VERSION,
+};
 use borsh::BorshDeserialize;
 use solana_program::{
-    account_info::AccountInfo, msg, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+    account_info::AccountInfo,
+    clock::Clock,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    sysvar::Sysvar,
 };
 
+/// canonical_mints orders an unordered pair of mints as mint A and mint B of
+/// an exchange booth. Mint A is the mint with the smaller pubkey
+pub fn canonical_mints(mint_x: &Pubkey, mint_y: &Pubkey) -> (Pubkey, Pubkey) {
+    if mint_x <= mint_y {
+        (*mint_x, *mint_y)
+    } else {
+        (*mint_y, *mint_x)
+    }
+}
+
+/// find_exchange_booth_address resolves the exchange booth of the admin for
+/// an unordered pair of mints. It returns the address and bump seed of the
+/// exchange booth together with its mint A and mint B
+pub fn find_exchange_booth_address(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+) -> (Pubkey, u8, Pubkey, Pubkey) {
+    let (mint_a, mint_b) = canonical_mints(mint_x, mint_y);
+    let (xbooth_pda, xbooth_bump_seed) = Pubkey::find_program_address(
+        &[b"xbooth", admin.as_ref(), mint_a.as_ref(), mint_b.as_ref()],
+        program_id,
+    );
+    (xbooth_pda, xbooth_bump_seed, mint_a, mint_b)
+}
+
+/// find_oracle_address returns the address and bump seed of the oracle
+/// publishing the price of the pair mint_a/mint_b for the admin
+pub fn find_oracle_address(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"xbooth_oracle",
+            admin.as_ref(),
+            mint_a.as_ref(),
+            mint_b.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// find_vault_address returns the address and bump seed of the vault
+/// holding tokens of mint for the exchange booth of the admin
+pub fn find_vault_address(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    mint: &Pubkey,
+    exchange_booth: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"xbooth",
+            admin.as_ref(),
+            mint.as_ref(),
+            exchange_booth.as_ref(),
+        ],
+        program_id,
+    )
+}
+
 pub fn get_exchange_booth_pda(
     program_id: &Pubkey,
     exchange_booth_account: &AccountInfo,
-    owner: &AccountInfo,
+    admin: &Pubkey,
     mint_a: &AccountInfo,
     mint_b: &AccountInfo,
 ) -> Result<(Pubkey, u8), ProgramError> {
     let (xbooth_pda, xbooth_bump_seed) = Pubkey::find_program_address(
         &[
             b"xbooth",
-            owner.key.as_ref(),
+            admin.as_ref(),
             mint_a.key.as_ref(),
             mint_b.key.as_ref(),
         ],
@@ -25,24 +99,136 @@ pub fn get_exchange_booth_pda(
     // check if correct public key
     if xbooth_pda != *exchange_booth_account.key {
         msg!("Invalid account key for exchange booth");
-        return Err(XBoothError::InvalidVaultAccount.into());
+        return Err(XBoothError::InvalidAccountAddress.into());
     }
     Ok((xbooth_pda, xbooth_bump_seed))
 }
 
-pub fn check_stored_owner(
+/// check_exchange_booth_pda verifies the address of the exchange booth with
+/// the bump seed stored at initialization instead of searching for it
+pub fn check_exchange_booth_pda(
+    program_id: &Pubkey,
     exchange_booth_account: &AccountInfo,
+    exchange_booth_data: &ExchangeBoothAccount,
+    mint_a: &AccountInfo,
+    mint_b: &AccountInfo,
+) -> Result<(), ProgramError> {
+    let xbooth_pda = Pubkey::create_program_address(
+        &[
+            b"xbooth",
+            exchange_booth_data.seed_admin.as_ref(),
+            mint_a.key.as_ref(),
+            mint_b.key.as_ref(),
+            &[exchange_booth_data.booth_bump],
+        ],
+        program_id,
+    )
+    .map_err(|_| {
+        msg!("Invalid bump seed for exchange booth");
+        XBoothError::InvalidAccountAddress
+    })?;
+
+    if xbooth_pda != *exchange_booth_account.key {
+        msg!("Invalid account key for exchange booth");
+        return Err(XBoothError::InvalidAccountAddress.into());
+    }
+    Ok(())
+}
+
+pub fn check_stored_owner(
+    exchange_booth_data: &ExchangeBoothAccount,
     authority: &AccountInfo,
 ) -> Result<(), ProgramError> {
-    let data = &mut (*exchange_booth_account.data).borrow_mut();
-    let exchange_booth_account_data = ExchangeBoothAccount::try_from_slice(&data).unwrap();
-    if authority.key != &exchange_booth_account_data.admin {
+    if authority.key != &exchange_booth_data.admin {
         msg!("authority is not stored as sole owner of the exchange_booth_account");
         return Err(XBoothError::InvalidOwner.into());
     }
     Ok(())
 }
 
+/// unpack_exchange_booth deserializes the exchange booth account data
+pub fn unpack_exchange_booth(
+    exchange_booth_account: &AccountInfo,
+) -> Result<ExchangeBoothAccount, ProgramError> {
+    ExchangeBoothAccount::try_from_slice(&exchange_booth_account.try_borrow_data()?).map_err(
+        |err| {
+            msg!("exchange booth account is not initialized. cause {:}", err);
+            XBoothError::AccountNotInitialized.into()
+        },
+    )
+}
+
+/// load_exchange_booth checks that the exchange booth account is an initialized
+/// exchange booth owned by the program and returns its state. Every instruction
+/// operating on an existing exchange booth loads it through here
+pub fn load_exchange_booth(
+    program_id: &Pubkey,
+    exchange_booth_account: &AccountInfo,
+) -> Result<ExchangeBoothAccount, ProgramError> {
+    if exchange_booth_account.owner != program_id {
+        msg!("exchange booth account is not owned by the program");
+        return Err(XBoothError::InvalidAccountAddress.into());
+    }
+
+    if exchange_booth_account.data_len() == EXCHANGE_BOOTH_ACCOUNT_V1_LEN {
+        msg!("exchange booth uses the v1 layout and needs to be migrated");
+        return Err(XBoothError::InvalidAccountVersion.into());
+    }
+
+    let exchange_booth_data = unpack_exchange_booth(exchange_booth_account)?;
+    if exchange_booth_data.version != EXCHANGE_BOOTH_ACCOUNT_VERSION {
+        msg!(
+            "exchange booth version {} is not supported, expected {}",
+            exchange_booth_data.version,
+            EXCHANGE_BOOTH_ACCOUNT_VERSION
+        );
+        return Err(XBoothError::InvalidAccountVersion.into());
+    }
+
+    if !exchange_booth_data.is_initialized {
+        msg!("exchange booth account is not initialized");
+        return Err(XBoothError::AccountNotInitialized.into());
+    }
+    Ok(exchange_booth_data)
+}
+
+/// check_vault checks that vault is the vault stored in the exchange booth,
+/// and that it is an initialized spl token account of mint owned by the
+/// exchange booth. It returns the token account state of the vault
+pub fn check_vault(
+    exchange_booth_account: &AccountInfo,
+    vault: &AccountInfo,
+    stored_vault: &Pubkey,
+    mint: &AccountInfo,
+) -> Result<spl_token::state::Account, ProgramError> {
+    if vault.key != stored_vault {
+        msg!("vault {} is not a vault of the exchange booth", vault.key);
+        return Err(XBoothError::InvalidVaultAccount.into());
+    }
+
+    if *vault.owner != spl_token::id() {
+        msg!("vault {} is not owned by the token program", vault.key);
+        return Err(XBoothError::InvalidSPLTokenAccount.into());
+    }
+
+    let vault_data = spl_token::state::Account::unpack_unchecked(&vault.try_borrow_data()?)?;
+    if !vault_data.is_initialized() {
+        msg!("vault {} is not initialized", vault.key);
+        return Err(XBoothError::AccountNotInitialized.into());
+    }
+
+    if vault_data.mint != *mint.key {
+        msg!("vault {} does not hold mint {}", vault.key, mint.key);
+        return Err(XBoothError::InvalidMint.into());
+    }
+
+    if vault_data.owner != *exchange_booth_account.key {
+        msg!("vault {} is not owned by the exchange booth", vault.key);
+        return Err(XBoothError::InvalidVaultAccount.into());
+    }
+    Ok(vault_data)
+}
+
 pub fn get_vault_pda(
     program_id: &Pubkey,
     exchange_booth_account: &AccountInfo,
@@ -61,16 +247,149 @@ pub fn get_vault_pda(
     );
     // check if correct public key
     if vault_pda != *vault.key {
-        msg!("Invalid account key for vault b");
+        msg!("Invalid account key for vault");
         return Err(XBoothError::InvalidVaultAccount.into());
     }
     Ok((vault_pda, vault_b_bump_seed))
 }
 
-pub fn amount_to_lamports(mint: &AccountInfo, amount: f64) -> Result<u64, ProgramError> {
-    let mint_account_data = spl_token::state::Mint::unpack_from_slice(&mint.try_borrow_data()?)?;
-    let mint_decimals = mint_account_data.decimals;
+/// check_exchange_rate makes sure the rate A/B is a valid, non zero fraction
+pub fn check_exchange_rate(rate_numerator: u64, rate_denominator: u64) -> Result<(), ProgramError> {
+    if rate_numerator == 0 || rate_denominator == 0 {
+        msg!("exchange rate numerator and denominator must be non zero");
+        return Err(XBoothError::InvalidExchangeRate.into());
+    }
+    Ok(())
+}
+
+/// check_fee makes sure the fee is at most 100%
+pub fn check_fee(fee_bps: u16) -> Result<(), ProgramError> {
+    // a fee of 100% leaves nothing to exchange
+    if fee_bps >= 10_000 {
+        msg!("fee of {} bps is not below 100%", fee_bps);
+        return Err(XBoothError::InvalidFee.into());
+    }
+    Ok(())
+}
 
-    let lamports = (amount * f64::powf(10., mint_decimals.into())) as u64;
-    Ok(lamports)
+pub fn get_oracle_pda(
+    program_id: &Pubkey,
+    oracle_account: &AccountInfo,
+    admin: &AccountInfo,
+    mint_a: &AccountInfo,
+    mint_b: &AccountInfo,
+) -> Result<(Pubkey, u8), ProgramError> {
+    let (oracle_pda, oracle_bump_seed) = Pubkey::find_program_address(
+        &[
+            b"xbooth_oracle",
+            admin.key.as_ref(),
+            mint_a.key.as_ref(),
+            mint_b.key.as_ref(),
+        ],
+        program_id,
+    );
+
+    // check if correct public key
+    if oracle_pda != *oracle_account.key {
+        msg!("Invalid account key for oracle");
+        return Err(XBoothError::InvalidOracleAccount.into());
+    }
+    Ok((oracle_pda, oracle_bump_seed))
+}
+
+/// get_oracle_rate reads the price from the oracle trusted by the exchange booth
+/// and returns it as the fraction (rate_numerator, rate_denominator).
+/// The price is rejected if it is stale or the confidence interval is too wide
+pub fn get_oracle_rate(
+    program_id: &Pubkey,
+    exchange_booth_data: &ExchangeBoothAccount,
+    oracle_account: &AccountInfo,
+) -> Result<(u64, u64), ProgramError> {
+    if *oracle_account.key != exchange_booth_data.oracle {
+        msg!("oracle account is not the oracle trusted by the exchange booth");
+        return Err(XBoothError::InvalidOracleAccount.into());
+    }
+
+    if oracle_account.owner != program_id {
+        msg!("oracle account is not owned by the program");
+        return Err(XBoothError::InvalidOracleAccount.into());
+    }
+
+    let oracle_data = OracleAccount::try_from_slice(&oracle_account.data.borrow())?;
+    check_oracle_price(exchange_booth_data, &oracle_data, Clock::get()?.slot)?;
+    oracle_rate(&oracle_data)
+}
+
+/// check_oracle_price rejects an oracle price that is stale at current_slot
+/// or whose confidence interval is too wide for the exchange booth
+pub fn check_oracle_price(
+    exchange_booth_data: &ExchangeBoothAccount,
+    oracle_data: &OracleAccount,
+    current_slot: u64,
+) -> Result<(), ProgramError> {
+    if current_slot.saturating_sub(oracle_data.publish_slot)
+        > exchange_booth_data.max_oracle_staleness
+    {
+        msg!(
+            "oracle price published at slot {} is stale at slot {}",
+            oracle_data.publish_slot,
+            current_slot
+        );
+        return Err(XBoothError::StaleOraclePrice.into());
+    }
+
+    if oracle_data.price == 0 {
+        msg!("oracle price is zero");
+        return Err(XBoothError::InvalidExchangeRate.into());
+    }
+
+    let conf_bps = u128::from(oracle_data.conf) * 10_000;
+    let max_conf_bps =
+        u128::from(oracle_data.price) * u128::from(exchange_booth_data.max_oracle_confidence_bps);
+    if conf_bps > max_conf_bps {
+        msg!(
+            "oracle confidence {} is too wide for price {}",
+            oracle_data.conf,
+            oracle_data.price
+        );
+        return Err(XBoothError::OracleConfidenceTooWide.into());
+    }
+    Ok(())
+}
+
+/// oracle_rate converts the oracle price into the exchange rate
+/// (rate_numerator, rate_denominator)
+pub fn oracle_rate(oracle_data: &OracleAccount) -> Result<(u64, u64), ProgramError> {
+    let scale = 10u64
+        .checked_pow(oracle_data.expo.unsigned_abs())
+        .ok_or(XBoothError::AmountOverflow)?;
+    if oracle_data.expo >= 0 {
+        let rate_numerator = oracle_data
+            .price
+            .checked_mul(scale)
+            .ok_or(XBoothError::AmountOverflow)?;
+        Ok((rate_numerator, 1))
+    } else {
+        Ok((oracle_data.price, scale))
+    }
+}
+
+/// check_mint makes sure the account is an initialized spl token mint
+pub fn check_mint(mint: &AccountInfo) -> Result<(), ProgramError> {
+    if *mint.owner != spl_token::id() {
+        msg!("mint {} is not owned by the token program", mint.key);
+        return Err(XBoothError::InvalidMint.into());
+    }
+
+    spl_token::state::Mint::unpack(&mint.try_borrow_data()?).map_err(|_| {
+        msg!("mint {} is not an initialized mint", mint.key);
+        XBoothError::InvalidMint
+    })?;
+    Ok(())
+}
+
+/// get_mint_decimals returns the number of decimals of the mint
+pub fn get_mint_decimals(mint: &AccountInfo) -> Result<u8, ProgramError> {
+    let mint_account_data = spl_token::state::Mint::unpack_from_slice(&mint.try_borrow_data()?)?;
+    Ok(mint_account_data.decimals)
 }
@@ -1,4 +1,3 @@
-use solana_program::program_pack::Pack;
 use solana_program::{
     account_info::next_account_info,
     account_info::AccountInfo,
@@ -9,10 +8,11 @@ use solana_program::{
 };
 
 use crate::errors::XBoothError;
+use crate::events::XBoothEvent;
 use crate::processor::utils;
 
 /// process will withdraw amount from an account
-pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: f64) -> ProgramResult {
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> ProgramResult {
     let accounts_iter = &mut accounts.iter();
     let exchange_booth_account = next_account_info(accounts_iter)?;
     let authority_account = next_account_info(accounts_iter)?;
@@ -34,11 +34,6 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: f64) -> Pr
         return Err(XBoothError::AccountIsNotWritable.into());
     }
 
-    if !token_account.is_signer {
-        msg!("token account needs to be signer");
-        return Err(XBoothError::AccountIsNotSigner.into());
-    }
-
     if !token_account.is_writable {
         msg!("token accounts must be writable");
         return Err(XBoothError::AccountIsNotWritable.into());
@@ -49,36 +44,42 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: f64) -> Pr
         return Err(XBoothError::AccountIsNotWritable.into());
     }
 
-    // check ownership of exchange booth
-    let (_xbooth_pda, xbooth_bump) = utils::get_exchange_booth_pda(
+    // check stored admin/owner of exchange booth
+    let xbooth_data = utils::load_exchange_booth(program_id, exchange_booth_account)?;
+    utils::check_stored_owner(&xbooth_data, authority_account)?;
+    utils::check_exchange_booth_pda(
         program_id,
         exchange_booth_account,
-        authority_account,
+        &xbooth_data,
         mint_a,
         mint_b,
-    )
-    .unwrap();
+    )?;
 
-    // check ownership of vault
-    let (_vault_pda, vault_bump) = utils::get_vault_pda(
-        program_id,
-        exchange_booth_account,
-        authority_account,
-        mint_a,
-        vault_account,
-    )
-    .unwrap();
-
-    // check stored admin/owner of exchange booth
-    utils::check_stored_owner(exchange_booth_account, authority_account).unwrap();
+    // decide which of the two vaults is withdrawn from
+    let (stored_vault, vault_mint, fees) = if *vault_account.key == xbooth_data.vault_a {
+        (&xbooth_data.vault_a, mint_a, xbooth_data.fees_a)
+    } else if *vault_account.key == xbooth_data.vault_b {
+        (&xbooth_data.vault_b, mint_b, xbooth_data.fees_b)
+    } else {
+        msg!("vault is not a vault of the exchange booth");
+        return Err(XBoothError::InvalidVaultAccount.into());
+    };
 
     // * withdraw money from vault into token_account using spl program
     // Check amount in vault
-    let vault_account_data =
-        spl_token::state::Account::unpack(&vault_account.data.borrow()).unwrap();
+    let vault_account_data = utils::check_vault(
+        exchange_booth_account,
+        vault_account,
+        stored_vault,
+        vault_mint,
+    )?;
 
-    let amount_lamports = utils::amount_to_lamports(mint_a, amount).unwrap();
-    if amount_lamports > vault_account_data.amount {
+    // the collected fees are withdrawn separately from the principal
+    let principal = vault_account_data
+        .amount
+        .checked_sub(fees)
+        .ok_or(XBoothError::InsufficientFunds)?;
+    if amount > principal {
         msg!("insufficient funds in vault accounts");
         return Err(XBoothError::InsufficientFunds.into());
     }
@@ -89,9 +90,8 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: f64) -> Pr
         &token_account.key,
         &exchange_booth_account.key,
         &[],
-        amount_lamports,
-    )
-    .unwrap();
+        amount,
+    )?;
 
     invoke_signed(
         &transfer_ix,
@@ -103,13 +103,19 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], amount: f64) -> Pr
         ],
         &[&[
             b"xbooth",
-            authority_account.key.as_ref(),
+            xbooth_data.seed_admin.as_ref(),
             mint_a.key.as_ref(),
             mint_b.key.as_ref(),
-            &[xbooth_bump],
+            &[xbooth_data.booth_bump],
         ]],
-    )
-    .unwrap();
+    )?;
 
-    Ok(())
+    XBoothEvent::Withdrawn {
+        booth: *exchange_booth_account.key,
+        mint: *vault_mint.key,
+        vault: *vault_account.key,
+        token_account: *token_account.key,
+        amount,
+    }
+    .emit()
 }
@@ -0,0 +1,122 @@
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::next_account_info, account_info::AccountInfo, entrypoint::ProgramResult, msg,
+    program::invoke_signed, pubkey::Pubkey,
+};
+
+use crate::errors::XBoothError;
+use crate::events::XBoothEvent;
+use crate::processor::utils;
+
+/// process will transfer the fees collected in both vaults to the
+/// token accounts given by the admin
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let exchange_booth_account = next_account_info(accounts_iter)?;
+    let authority_account = next_account_info(accounts_iter)?;
+    let vault_a = next_account_info(accounts_iter)?;
+    let vault_b = next_account_info(accounts_iter)?;
+    let token_account_a = next_account_info(accounts_iter)?;
+    let token_account_b = next_account_info(accounts_iter)?;
+    let mint_a = next_account_info(accounts_iter)?;
+    let mint_b = next_account_info(accounts_iter)?;
+    let token_program = next_account_info(accounts_iter)?;
+
+    // * Checks
+    if !authority_account.is_signer {
+        msg!("authority is not a signer!");
+        return Err(XBoothError::AccountIsNotSigner.into());
+    }
+
+    if !exchange_booth_account.is_writable {
+        msg!("Exchange booth needs to be writable");
+        return Err(XBoothError::ExchangeBoothNotWritable.into());
+    }
+
+    if !vault_a.is_writable || !vault_b.is_writable {
+        msg!("vaults need to be writable");
+        return Err(XBoothError::AccountIsNotWritable.into());
+    }
+
+    if !token_account_a.is_writable || !token_account_b.is_writable {
+        msg!("receiving token accounts must be writable");
+        return Err(XBoothError::AccountIsNotWritable.into());
+    }
+
+    let mut xbooth_data = utils::load_exchange_booth(program_id, exchange_booth_account)?;
+    utils::check_stored_owner(&xbooth_data, authority_account)?;
+    utils::check_exchange_booth_pda(
+        program_id,
+        exchange_booth_account,
+        &xbooth_data,
+        mint_a,
+        mint_b,
+    )?;
+    utils::check_vault(
+        exchange_booth_account,
+        vault_a,
+        &xbooth_data.vault_a,
+        mint_a,
+    )?;
+    utils::check_vault(
+        exchange_booth_account,
+        vault_b,
+        &xbooth_data.vault_b,
+        mint_b,
+    )?;
+
+    let xbooth_seeds: &[&[u8]] = &[
+        b"xbooth",
+        xbooth_data.seed_admin.as_ref(),
+        mint_a.key.as_ref(),
+        mint_b.key.as_ref(),
+        &[xbooth_data.booth_bump],
+    ];
+
+    // * Transfer the fees out of the vaults
+    for (vault, token_account, fees) in [
+        (vault_a, token_account_a, xbooth_data.fees_a),
+        (vault_b, token_account_b, xbooth_data.fees_b),
+    ] {
+        if fees == 0 {
+            continue;
+        }
+
+        verbose_msg!(
+            "transfer fees {} from vault {} to {}",
+            fees,
+            vault.key,
+            token_account.key
+        );
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                vault.key,
+                token_account.key,
+                exchange_booth_account.key,
+                &[],
+                fees,
+            )?,
+            &[
+                token_program.clone(),
+                vault.clone(),
+                token_account.clone(),
+                exchange_booth_account.clone(),
+            ],
+            &[xbooth_seeds],
+        )?;
+    }
+
+    let (amount_a, amount_b) = (xbooth_data.fees_a, xbooth_data.fees_b);
+    xbooth_data.fees_a = 0;
+    xbooth_data.fees_b = 0;
+    xbooth_data.serialize(&mut &mut exchange_booth_account.data.borrow_mut()[..])?;
+
+    XBoothEvent::FeesWithdrawn {
+        booth: *exchange_booth_account.key,
+        admin: *authority_account.key,
+        amount_a,
+        amount_b,
+    }
+    .emit()
+}
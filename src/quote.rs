@@ -0,0 +1,224 @@
+//! Exchange math of the exchange booth. The functions only use core integer
+//! arithmetic and never touch accounts or the runtime, so the program and
+//! off-chain code compute the same amounts and rounding for an exchange.
+//! Errors are QuoteError, which only depends on core as well and converts
+//! into XBoothError.
+
+use core::fmt;
+
+/// QuoteError is the reason an exchange cannot be quoted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuoteError {
+    AmountOverflow,
+    InvalidExchangeRate,
+    InvalidFee,
+    SlippageExceeded,
+}
+
+impl fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            QuoteError::AmountOverflow => "Amount overflow",
+            QuoteError::InvalidExchangeRate => "Invalid exchange rate",
+            QuoteError::InvalidFee => "Invalid fee",
+            QuoteError::SlippageExceeded => "Exchange output is below the minimum amount",
+        })
+    }
+}
+
+/// ExchangeMode decides which side of the exchange is fixed by the trader
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExchangeMode {
+    /// send exactly amount_in and receive at least min_amount_out
+    ExactIn { amount_in: u64, min_amount_out: u64 },
+    /// receive exactly amount_out and send at most max_amount_in
+    ExactOut { amount_out: u64, max_amount_in: u64 },
+}
+
+/// Quote is the result of an exchange, amounts are in base units of the mints
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quote {
+    /// tokens sent by the trader
+    pub amount_in: u64,
+    /// tokens received by the trader
+    pub amount_out: u64,
+    /// fee kept in the out vault
+    pub fee: u64,
+}
+
+impl Quote {
+    /// gross_amount_out is the amount that leaves the available balance of
+    /// the out vault, the fee stays in the vault but is set aside as fees.
+    /// It never overflows since the quote derives both from a u64 amount
+    pub fn gross_amount_out(&self) -> u64 {
+        self.amount_out + self.fee
+    }
+}
+
+/// quote computes the amounts of an exchange at the rate in/out given as
+/// rate_numerator / rate_denominator whole tokens. The fee is charged on the
+/// output and every rounding is in favor of the exchange booth
+pub fn quote(
+    mode: ExchangeMode,
+    rate_numerator: u64,
+    rate_denominator: u64,
+    decimals_in: u8,
+    decimals_out: u8,
+    fee_bps: u16,
+) -> Result<Quote, QuoteError> {
+    match mode {
+        ExchangeMode::ExactIn { amount_in, .. } => {
+            let gross_amount_out = exchange_amount(
+                amount_in,
+                rate_numerator,
+                rate_denominator,
+                decimals_in,
+                decimals_out,
+            )?;
+            let fee = fee_amount(gross_amount_out, fee_bps)?;
+            let amount_out = gross_amount_out
+                .checked_sub(fee)
+                .ok_or(QuoteError::AmountOverflow)?;
+            Ok(Quote {
+                amount_in,
+                amount_out,
+                fee,
+            })
+        }
+        ExchangeMode::ExactOut { amount_out, .. } => {
+            let gross_amount_out = amount_before_fee(amount_out, fee_bps)?;
+            let amount_in = exchange_amount_in(
+                gross_amount_out,
+                rate_numerator,
+                rate_denominator,
+                decimals_in,
+                decimals_out,
+            )?;
+            Ok(Quote {
+                amount_in,
+                amount_out,
+                fee: gross_amount_out - amount_out,
+            })
+        }
+    }
+}
+
+/// check_slippage makes sure the quote respects the limit of the mode
+pub fn check_slippage(mode: ExchangeMode, quote: &Quote) -> Result<(), QuoteError> {
+    let within_limit = match mode {
+        ExchangeMode::ExactIn { min_amount_out, .. } => quote.amount_out >= min_amount_out,
+        ExchangeMode::ExactOut { max_amount_in, .. } => quote.amount_in <= max_amount_in,
+    };
+    if !within_limit {
+        return Err(QuoteError::SlippageExceeded);
+    }
+    Ok(())
+}
+
+/// rate_in_out returns the rate of an exchange from the rate A/B of the
+/// exchange booth. Exchanging B for A uses the inverse rate
+pub fn rate_in_out(rate_numerator: u64, rate_denominator: u64, is_a_to_b: bool) -> (u64, u64) {
+    if is_a_to_b {
+        (rate_numerator, rate_denominator)
+    } else {
+        (rate_denominator, rate_numerator)
+    }
+}
+
+/// available_amount is the balance of a vault that exchanges can use,
+/// the collected fees and the min reserve cannot be exchanged
+pub fn available_amount(balance: u64, fees: u64, min_reserve: u64) -> u64 {
+    balance.saturating_sub(fees).saturating_sub(min_reserve)
+}
+
+/// fee_amount returns the fee in basis points of amount,
+/// rounded up in favor of the exchange booth
+pub fn fee_amount(amount: u64, fee_bps: u16) -> Result<u64, QuoteError> {
+    let fee = div_ceil(u128::from(amount) * u128::from(fee_bps), 10_000);
+    u64::try_from(fee).map_err(|_| QuoteError::AmountOverflow)
+}
+
+/// amount_before_fee returns the smallest gross amount that is at least
+/// amount after fee_amount is deducted
+pub fn amount_before_fee(amount: u64, fee_bps: u16) -> Result<u64, QuoteError> {
+    // a fee of 100% leaves nothing to exchange
+    if fee_bps >= 10_000 {
+        return Err(QuoteError::InvalidFee);
+    }
+
+    let remainder_bps = 10_000 - u128::from(fee_bps);
+    let gross = div_ceil(u128::from(amount) * 10_000, remainder_bps);
+    u64::try_from(gross).map_err(|_| QuoteError::AmountOverflow)
+}
+
+/// exchange_amount converts amount_in base units of the in mint into base units
+/// of the out mint at the rate in/out given as rate_numerator / rate_denominator
+/// whole tokens. The result is rounded down in favor of the exchange booth
+pub fn exchange_amount(
+    amount_in: u64,
+    rate_numerator: u64,
+    rate_denominator: u64,
+    decimals_in: u8,
+    decimals_out: u8,
+) -> Result<u64, QuoteError> {
+    let scale_in = 10u128
+        .checked_pow(decimals_in.into())
+        .ok_or(QuoteError::AmountOverflow)?;
+    let scale_out = 10u128
+        .checked_pow(decimals_out.into())
+        .ok_or(QuoteError::AmountOverflow)?;
+
+    let numerator = u128::from(amount_in)
+        .checked_mul(rate_numerator.into())
+        .and_then(|n| n.checked_mul(scale_out))
+        .ok_or(QuoteError::AmountOverflow)?;
+    let denominator = u128::from(rate_denominator)
+        .checked_mul(scale_in)
+        .ok_or(QuoteError::AmountOverflow)?;
+    if denominator == 0 {
+        return Err(QuoteError::InvalidExchangeRate);
+    }
+
+    u64::try_from(numerator / denominator).map_err(|_| QuoteError::AmountOverflow)
+}
+
+/// exchange_amount_in is the inverse of exchange_amount. It returns the
+/// amount of the in mint needed to receive amount_out of the out mint and
+/// rounds up in favor of the exchange booth
+pub fn exchange_amount_in(
+    amount_out: u64,
+    rate_numerator: u64,
+    rate_denominator: u64,
+    decimals_in: u8,
+    decimals_out: u8,
+) -> Result<u64, QuoteError> {
+    let scale_in = 10u128
+        .checked_pow(decimals_in.into())
+        .ok_or(QuoteError::AmountOverflow)?;
+    let scale_out = 10u128
+        .checked_pow(decimals_out.into())
+        .ok_or(QuoteError::AmountOverflow)?;
+
+    let numerator = u128::from(amount_out)
+        .checked_mul(rate_denominator.into())
+        .and_then(|n| n.checked_mul(scale_in))
+        .ok_or(QuoteError::AmountOverflow)?;
+    let denominator = u128::from(rate_numerator)
+        .checked_mul(scale_out)
+        .ok_or(QuoteError::AmountOverflow)?;
+    if denominator == 0 {
+        return Err(QuoteError::InvalidExchangeRate);
+    }
+
+    u64::try_from(div_ceil(numerator, denominator)).map_err(|_| QuoteError::AmountOverflow)
+}
+
+/// div_ceil divides n by d and rounds the result up, d must not be zero
+fn div_ceil(n: u128, d: u128) -> u128 {
+    let quotient = n / d;
+    if quotient * d < n {
+        quotient + 1
+    } else {
+        quotient
+    }
+}
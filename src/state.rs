@@ -3,11 +3,131 @@ use std::mem::size_of;
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
 
+/// version of the ExchangeBoothAccount layout. The first byte of the account
+/// identifies the layout, booths created before the version was stored
+/// use the ExchangeBoothAccountV1 layout and have to be migrated
+pub const EXCHANGE_BOOTH_ACCOUNT_VERSION: u8 = 2;
+
 #[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
 pub struct ExchangeBoothAccount {
+    /// discriminator and layout version, EXCHANGE_BOOTH_ACCOUNT_VERSION
+    pub version: u8,
+    pub is_initialized: bool,
     pub admin: Pubkey,
     pub vault_a: Pubkey,
     pub vault_b: Pubkey,
+    /// exchange rate A/B as a fraction: one token A buys
+    /// rate_numerator / rate_denominator tokens B. Mint A is the mint
+    /// with the smaller pubkey, see utils::canonical_mints
+    pub rate_numerator: u64,
+    pub rate_denominator: u64,
+    /// oracle account used to price exchanges, the fixed rate above
+    /// is used if the oracle is the default pubkey
+    pub oracle: Pubkey,
+    /// max number of slots since the oracle price was published
+    pub max_oracle_staleness: u64,
+    /// max width of the oracle confidence interval in basis points of the price
+    pub max_oracle_confidence_bps: u16,
+    /// fee charged on the output of an exchange in basis points
+    pub fee_bps: u16,
+    /// fees collected in vault A and vault B that are not yet withdrawn
+    pub fees_a: u64,
+    pub fees_b: u64,
+    /// min balance kept in vault A and vault B that exchanges cannot use
+    pub min_reserve_a: u64,
+    pub min_reserve_b: u64,
+    /// bump seeds of the exchange booth and vault pdas found at initialization
+    pub booth_bump: u8,
+    pub vault_a_bump: u8,
+    pub vault_b_bump: u8,
+    /// admin that created the exchange booth. The exchange booth pda is seeded
+    /// with it, so it is kept after the admin is handed over
+    pub seed_admin: Pubkey,
+    /// admin proposed by the current admin, the default pubkey if none
+    pub pending_admin: Pubkey,
+    /// exchanges are rejected while the admin paused the exchange booth
+    pub is_paused: bool,
+    /// reserved for fields added in later versions. Split in two since borsh
+    /// does not implement arrays of every length
+    pub padding: [u8; 31],
+    pub padding_ext: [u8; 32],
+}
+
+pub const EXCHANGE_BOOTH_ACCOUNT_LEN: usize = size_of::<u8>()
+    + size_of::<bool>()
+    + size_of::<Pubkey>() * 4
+    + size_of::<u64>() * 7
+    + size_of::<u16>() * 2
+    + size_of::<u8>() * 3
+    + size_of::<Pubkey>() * 2
+    + size_of::<bool>()
+    + size_of::<[u8; 31]>()
+    + size_of::<[u8; 32]>();
+
+impl ExchangeBoothAccount {
+    /// from_v1 upgrades the state of a booth created with the V1 layout.
+    /// The V1 layout stores neither the exchange rate, the fee nor the bump
+    /// seeds, so the caller provides them
+    pub fn from_v1(
+        booth: ExchangeBoothAccountV1,
+        rate_numerator: u64,
+        rate_denominator: u64,
+        fee_bps: u16,
+        booth_bump: u8,
+        vault_a_bump: u8,
+        vault_b_bump: u8,
+    ) -> Self {
+        ExchangeBoothAccount {
+            version: EXCHANGE_BOOTH_ACCOUNT_VERSION,
+            is_initialized: true,
+            admin: booth.admin,
+            vault_a: booth.vault_a,
+            vault_b: booth.vault_b,
+            rate_numerator,
+            rate_denominator,
+            oracle: Pubkey::default(),
+            max_oracle_staleness: 0,
+            max_oracle_confidence_bps: 0,
+            fee_bps,
+            fees_a: 0,
+            fees_b: 0,
+            min_reserve_a: 0,
+            min_reserve_b: 0,
+            booth_bump,
+            vault_a_bump,
+            vault_b_bump,
+            seed_admin: booth.admin,
+            pending_admin: Pubkey::default(),
+            is_paused: false,
+            padding: [0; 31],
+            padding_ext: [0; 32],
+        }
+    }
+}
+
+/// ExchangeBoothAccountV1 is the unversioned layout of booths created
+/// before ExchangeBoothAccount stored a version. It only holds the admin and
+/// the vaults. Upgrade these booths with MigrateBooth
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct ExchangeBoothAccountV1 {
+    pub admin: Pubkey,
+    pub vault_a: Pubkey,
+    pub vault_b: Pubkey,
+}
+
+pub const EXCHANGE_BOOTH_ACCOUNT_V1_LEN: usize = size_of::<Pubkey>() * 3;
+
+/// OracleAccount is a simple price feed for a token pair A/B owned
+/// by the program and updated by its admin
+#[derive(Clone, Debug, BorshDeserialize, BorshSerialize)]
+pub struct OracleAccount {
+    pub admin: Pubkey,
+    /// price of one token A in token B is price * 10^expo
+    pub price: u64,
+    pub expo: i32,
+    /// confidence interval of the price, in the same unit as price
+    pub conf: u64,
+    pub publish_slot: u64,
 }
 
-pub const EXCHANGE_BOOTH_ACCOUNT_LEN: usize = size_of::<Pubkey>() * 3;
+pub const ORACLE_ACCOUNT_LEN: usize = size_of::<Pubkey>() + size_of::<u64>() * 3 + size_of::<i32>();
@@ -0,0 +1,337 @@
+#![cfg(feature = "cli")]
+use async_trait::async_trait;
+use serde_json::Value;
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    signature::{write_keypair_file, Keypair},
+    signer::Signer,
+    system_instruction,
+    transaction::Transaction,
+    transport::TransportError,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+use xbooth::cli::{self, CliError};
+use xbooth::client::{ClientError, XBoothClient, XBoothRpc};
+use xbooth::processor::utils::canonical_mints;
+use xbooth::processor::Processor;
+
+/// BanksRpc connects the cli to the bank of solana-program-test
+struct BanksRpc(BanksClient);
+
+#[async_trait]
+impl XBoothRpc for BanksRpc {
+    async fn get_account(&mut self, address: &Pubkey) -> Result<Option<Account>, ClientError> {
+        Ok(self
+            .0
+            .get_account(*address)
+            .await
+            .map_err(TransportError::from)?)
+    }
+
+    async fn get_slot(&mut self) -> Result<u64, ClientError> {
+        Ok(self.0.get_root_slot().await.map_err(TransportError::from)?)
+    }
+
+    async fn get_latest_blockhash(&mut self) -> Result<Hash, ClientError> {
+        Ok(self
+            .0
+            .get_latest_blockhash()
+            .await
+            .map_err(TransportError::from)?)
+    }
+
+    async fn send_transaction(&mut self, transaction: Transaction) -> Result<(), ClientError> {
+        Ok(self
+            .0
+            .process_transaction(transaction)
+            .await
+            .map_err(TransportError::from)?)
+    }
+}
+
+/// CliSetup holds the bank and the keypair files used by the commands
+struct CliSetup {
+    banks_client: BanksClient,
+    program_id: Pubkey,
+    keypair_path: String,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    token_account_a: Pubkey,
+    token_account_b: Pubkey,
+}
+
+/// keypair_file writes the keypair to a temporary file and returns its path
+fn keypair_file(keypair: &Keypair) -> String {
+    let path = std::env::temp_dir().join(format!("xbooth-cli-{}.json", keypair.pubkey()));
+    let path = path.to_str().unwrap().to_string();
+    write_keypair_file(keypair, &path).unwrap();
+    path
+}
+
+/// setup_cli creates two mints with 6 decimals and a funded token account of
+/// the admin for each mint
+async fn setup_cli() -> CliSetup {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new(
+        "xbooth",
+        program_id,
+        processor!(Processor::process_instruction),
+    );
+    let (banks_client, admin, _recent_blockhash) = program_test.start().await;
+    let rent = banks_client.clone().get_rent().await.unwrap();
+    let mut client = XBoothClient::new(BanksRpc(banks_client.clone()), program_id);
+
+    let (mint_x, mint_y) = (Keypair::new(), Keypair::new());
+    let (mint_a, mint_b) = canonical_mints(&mint_x.pubkey(), &mint_y.pubkey());
+    let (mint_a_keypair, mint_b_keypair) = if mint_a == mint_x.pubkey() {
+        (mint_x, mint_y)
+    } else {
+        (mint_y, mint_x)
+    };
+    let token_account_a = Keypair::new();
+    let token_account_b = Keypair::new();
+    for (mint, token_account) in [
+        (&mint_a_keypair, &token_account_a),
+        (&mint_b_keypair, &token_account_b),
+    ] {
+        let instructions = [
+            system_instruction::create_account(
+                &admin.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &admin.pubkey(),
+                None,
+                6,
+            )
+            .unwrap(),
+            system_instruction::create_account(
+                &admin.pubkey(),
+                &token_account.pubkey(),
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &token_account.pubkey(),
+                &mint.pubkey(),
+                &admin.pubkey(),
+            )
+            .unwrap(),
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &token_account.pubkey(),
+                &admin.pubkey(),
+                &[],
+                100_000_000,
+            )
+            .unwrap(),
+        ];
+        client
+            .send(&instructions, &admin, &[mint, token_account])
+            .await
+            .unwrap();
+    }
+
+    CliSetup {
+        banks_client,
+        program_id,
+        keypair_path: keypair_file(&admin),
+        mint_a,
+        mint_b,
+        token_account_a: token_account_a.pubkey(),
+        token_account_b: token_account_b.pubkey(),
+    }
+}
+
+/// run_cli runs a command with the admin keypair and returns its output
+async fn run_cli(setup: &CliSetup, args: &[&str], json: bool) -> Result<String, CliError> {
+    let program_id = setup.program_id.to_string();
+    let mut argv = vec![
+        "xbooth",
+        "--program-id",
+        &program_id,
+        "--keypair",
+        &setup.keypair_path,
+    ];
+    if json {
+        argv.push("--json");
+    }
+    argv.extend_from_slice(args);
+    let matches = cli::app().get_matches_from_safe(argv).unwrap();
+
+    let mut out = Vec::new();
+    cli::run(&matches, BanksRpc(setup.banks_client.clone()), &mut out).await?;
+    Ok(String::from_utf8(out).unwrap())
+}
+
+/// run_cli_json runs a command that has to succeed and parses its json output
+async fn run_cli_json(setup: &CliSetup, args: &[&str]) -> Value {
+    let output = run_cli(setup, args, true).await.unwrap();
+    serde_json::from_str(&output).unwrap()
+}
+
+/// token_balance returns the raw amount held by a token account
+async fn token_balance(setup: &CliSetup, token_account: &Pubkey) -> u64 {
+    let account = setup
+        .banks_client
+        .clone()
+        .get_account(*token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+#[tokio::test]
+async fn test_cli_booth_lifecycle() {
+    let setup = setup_cli().await;
+    let mint_a = setup.mint_a.to_string();
+    let mint_b = setup.mint_b.to_string();
+    let token_account_a = setup.token_account_a.to_string();
+    let token_account_b = setup.token_account_b.to_string();
+
+    // * TEST: the rate given for the mints B/A is stored inverted as A/B
+    let output = run_cli_json(
+        &setup,
+        &["init", &mint_b, &mint_a, "2", "3", "--fee-bps", "25"],
+    )
+    .await;
+    assert_eq!(output["mint_a"], mint_a.as_str());
+    assert_eq!(output["mint_b"], mint_b.as_str());
+    assert_eq!(output["rate_numerator"], 3);
+    assert_eq!(output["rate_denominator"], 2);
+    let booth = output["booth"].as_str().unwrap().to_string();
+
+    run_cli_json(&setup, &["deposit", &booth, &token_account_a, "40000000"]).await;
+    run_cli_json(&setup, &["deposit", &booth, &token_account_b, "50000000"]).await;
+
+    // * TEST: the exchange reports the amounts that were transferred
+    let balance_b = token_balance(&setup, &setup.token_account_b).await;
+    let output = run_cli_json(
+        &setup,
+        &[
+            "exchange",
+            &booth,
+            &token_account_a,
+            &token_account_b,
+            "10000000",
+            "--min-amount-out",
+            "14000000",
+        ],
+    )
+    .await;
+    assert_eq!(output["amount_in"], 10_000_000);
+    assert_eq!(output["amount_out"], 14_962_500);
+    assert_eq!(output["fee"], 37_500);
+    assert_eq!(
+        token_balance(&setup, &setup.token_account_b).await - balance_b,
+        14_962_500
+    );
+
+    // * TEST: show lists the balances of the vaults
+    let output = run_cli_json(&setup, &["show", &booth]).await;
+    assert_eq!(output["balance_a"], 50_000_000);
+    assert_eq!(output["balance_b"], 50_000_000 - 14_962_500);
+    assert_eq!(output["fees_b"], 37_500);
+    assert_eq!(output["is_paused"], false);
+
+    let output = run_cli_json(&setup, &["set-rate", &booth, "1", "1"]).await;
+    assert_eq!(output["rate_numerator"], 1);
+    let output = run_cli_json(&setup, &["show", &booth]).await;
+    assert_eq!(output["rate_numerator"], 1);
+    assert_eq!(output["rate_denominator"], 1);
+
+    // * TEST: withdraw into a token account given by its address
+    run_cli_json(&setup, &["withdraw", &booth, &token_account_b, "5000000"]).await;
+
+    // * TEST: without --json every field is printed on its own line
+    let output = run_cli(&setup, &["show", &booth], false).await.unwrap();
+    assert!(output.contains(&format!("mint_a: {}\n", mint_a)));
+    assert!(output.contains(&format!(
+        "balance_b: {}\n",
+        50_000_000 - 14_962_500 - 5_000_000
+    )));
+
+    // * TEST: closing returns the vaults including the fees to the admin
+    run_cli_json(
+        &setup,
+        &["close", &booth, &token_account_a, &token_account_b],
+    )
+    .await;
+    assert_eq!(
+        token_balance(&setup, &setup.token_account_a).await,
+        100_000_000
+    );
+    assert_eq!(
+        token_balance(&setup, &setup.token_account_b).await,
+        100_000_000
+    );
+    assert!(matches!(
+        run_cli(&setup, &["show", &booth], true).await,
+        Err(CliError::Client(ClientError::AccountNotFound(_)))
+    ));
+}
+
+#[tokio::test]
+async fn test_cli_invalid_arguments() {
+    let setup = setup_cli().await;
+    let mint_a = setup.mint_a.to_string();
+
+    assert!(matches!(
+        run_cli(&setup, &["init", &mint_a, "not-a-mint", "1", "1"], true).await,
+        Err(CliError::InvalidArgument("mint_y", _))
+    ));
+    assert!(matches!(
+        run_cli(
+            &setup,
+            &[
+                "init",
+                &mint_a,
+                &setup.mint_b.to_string(),
+                "1",
+                "1",
+                "--fee-bps",
+                "70000"
+            ],
+            true
+        )
+        .await,
+        Err(CliError::InvalidArgument("fee_bps", _))
+    ));
+    assert!(matches!(
+        run_cli(&setup, &["show", &Pubkey::new_unique().to_string()], true).await,
+        Err(CliError::Client(ClientError::AccountNotFound(_)))
+    ));
+
+    // * TEST: a missing keypair file is reported before anything is sent
+    let program_id = setup.program_id.to_string();
+    let matches = cli::app()
+        .get_matches_from_safe([
+            "xbooth",
+            "--program-id",
+            &program_id,
+            "--keypair",
+            "/nonexistent/id.json",
+            "set-rate",
+            &Pubkey::new_unique().to_string(),
+            "1",
+            "1",
+        ])
+        .unwrap();
+    let mut out = Vec::new();
+    assert!(matches!(
+        cli::run(&matches, BanksRpc(setup.banks_client.clone()), &mut out).await,
+        Err(CliError::Keypair(_, _))
+    ));
+}
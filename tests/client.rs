@@ -0,0 +1,585 @@
+#![cfg(feature = "client")]
+use async_trait::async_trait;
+use proptest::prelude::*;
+use solana_program::{program_error::ProgramError, program_pack::Pack, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    instruction::InstructionError,
+    signature::Keypair,
+    signer::Signer,
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+    transport::TransportError,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+use xbooth::client::{BoothView, ClientError, Quote, XBoothClient, XBoothRpc};
+use xbooth::errors::XBoothError;
+use xbooth::processor::exchange::ExchangeMode;
+use xbooth::processor::utils::{canonical_mints, find_oracle_address};
+use xbooth::processor::Processor;
+
+/// BanksRpc connects the client to the bank of solana-program-test
+struct BanksRpc(BanksClient);
+
+#[async_trait]
+impl XBoothRpc for BanksRpc {
+    async fn get_account(&mut self, address: &Pubkey) -> Result<Option<Account>, ClientError> {
+        Ok(self
+            .0
+            .get_account(*address)
+            .await
+            .map_err(TransportError::from)?)
+    }
+
+    async fn get_slot(&mut self) -> Result<u64, ClientError> {
+        Ok(self.0.get_root_slot().await.map_err(TransportError::from)?)
+    }
+
+    async fn get_latest_blockhash(&mut self) -> Result<Hash, ClientError> {
+        Ok(self
+            .0
+            .get_latest_blockhash()
+            .await
+            .map_err(TransportError::from)?)
+    }
+
+    async fn send_transaction(&mut self, transaction: Transaction) -> Result<(), ClientError> {
+        Ok(self
+            .0
+            .process_transaction(transaction)
+            .await
+            .map_err(TransportError::from)?)
+    }
+}
+
+/// ClientSetup holds a client together with an initialized exchange booth
+/// and a funded token account of the admin for each mint
+struct ClientSetup {
+    client: XBoothClient<BanksRpc>,
+    admin: Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    token_account_a: Keypair,
+    token_account_b: Keypair,
+}
+
+/// setup_client creates the mints and token accounts through the client and
+/// initializes an exchange booth for the pair with the rate 3/2 and fee_bps
+async fn setup_client(decimals_a: u8, decimals_b: u8, fee_bps: u16) -> ClientSetup {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new(
+        "xbooth",
+        program_id,
+        processor!(Processor::process_instruction),
+    );
+    let (banks_client, admin, _recent_blockhash) = program_test.start().await;
+    let rent = banks_client.clone().get_rent().await.unwrap();
+    let mut client = XBoothClient::new(BanksRpc(banks_client), program_id);
+
+    let (mint_x, mint_y) = (Keypair::new(), Keypair::new());
+    let (mint_a, mint_b) = canonical_mints(&mint_x.pubkey(), &mint_y.pubkey());
+    let (mint_a_keypair, mint_b_keypair) = if mint_a == mint_x.pubkey() {
+        (mint_x, mint_y)
+    } else {
+        (mint_y, mint_x)
+    };
+    let token_account_a = Keypair::new();
+    let token_account_b = Keypair::new();
+    for (mint, decimals, token_account) in [
+        (&mint_a_keypair, decimals_a, &token_account_a),
+        (&mint_b_keypair, decimals_b, &token_account_b),
+    ] {
+        let instructions = [
+            system_instruction::create_account(
+                &admin.pubkey(),
+                &mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &admin.pubkey(),
+                None,
+                decimals,
+            )
+            .unwrap(),
+            system_instruction::create_account(
+                &admin.pubkey(),
+                &token_account.pubkey(),
+                rent.minimum_balance(TokenAccount::LEN),
+                TokenAccount::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account(
+                &spl_token::id(),
+                &token_account.pubkey(),
+                &mint.pubkey(),
+                &admin.pubkey(),
+            )
+            .unwrap(),
+            spl_token::instruction::mint_to(
+                &spl_token::id(),
+                &mint.pubkey(),
+                &token_account.pubkey(),
+                &admin.pubkey(),
+                &[],
+                100 * 10u64.pow(decimals.into()),
+            )
+            .unwrap(),
+        ];
+        client
+            .send(&instructions, &admin, &[mint, token_account])
+            .await
+            .unwrap();
+    }
+
+    client
+        .initialize_exchange_booth(&admin, &mint_a, &mint_b, 3, 2, fee_bps)
+        .await
+        .unwrap();
+
+    ClientSetup {
+        client,
+        admin,
+        mint_a,
+        mint_b,
+        token_account_a,
+        token_account_b,
+    }
+}
+
+/// token_balance returns the raw amount held by a token account
+async fn token_balance(client: &mut XBoothClient<BanksRpc>, token_account: &Pubkey) -> u64 {
+    let account = client
+        .rpc
+        .get_account(token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+/// assert_client_error checks that a client call failed with the program error
+fn assert_client_error<T: std::fmt::Debug>(result: Result<T, ClientError>, error: XBoothError) {
+    match result {
+        Err(ClientError::Program(ProgramError::Custom(code))) => assert_eq!(code, error as u32),
+        other => panic!("expected {:?}, got {:?}", error, other),
+    }
+}
+
+/// program_error_code returns the custom program error of a failed quote or
+/// of a failed transaction
+fn program_error_code(error: &ClientError) -> Option<u32> {
+    match error {
+        ClientError::Program(ProgramError::Custom(code)) => Some(*code),
+        ClientError::Transport(TransportError::TransactionError(
+            TransactionError::InstructionError(_, InstructionError::Custom(code)),
+        )) => Some(*code),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn test_client_booth_lifecycle() {
+    let mut setup = setup_client(6, 9, 25).await;
+    let base_a = 10u64.pow(6);
+    let base_b = 10u64.pow(9);
+    let admin = setup.admin.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+    let client = &mut setup.client;
+
+    // * TEST: the booth is found for the mints in any order
+    let booth = client
+        .booth(&admin, &setup.mint_b, &setup.mint_a)
+        .await
+        .unwrap();
+    assert_eq!(booth.mint_a, setup.mint_a);
+    assert_eq!(booth.mint_b, setup.mint_b);
+    assert_eq!((booth.decimals_a, booth.decimals_b), (6, 9));
+    assert_eq!((booth.balance_a, booth.balance_b), (0, 0));
+    assert_eq!(booth.booth.admin, admin);
+
+    client
+        .deposit(
+            &setup.admin,
+            &booth,
+            &token_account_a,
+            &setup.mint_a,
+            40 * base_a,
+        )
+        .await
+        .unwrap();
+    client
+        .deposit(
+            &setup.admin,
+            &booth,
+            &token_account_b,
+            &setup.mint_b,
+            50 * base_b,
+        )
+        .await
+        .unwrap();
+    let booth = client.booth_at(&booth.address).await.unwrap();
+    assert_eq!(booth.balance_a, 40 * base_a);
+    assert_eq!(booth.balance_b, 50 * base_b);
+
+    // * TEST: the quote of an exchange A for B is what the program transfers
+    let mode = ExchangeMode::ExactIn {
+        amount_in: 4 * base_a + 3,
+        min_amount_out: 0,
+    };
+    let quote = booth.quote(&setup.mint_a, mode).unwrap();
+    // 4.000003 A buy 6.0000045 B, the fee of 0.25% is rounded up
+    let gross_amount_out = 6_000_004_500;
+    let fee = 15_000_012;
+    assert_eq!(
+        quote,
+        Quote {
+            amount_in: 4 * base_a + 3,
+            amount_out: gross_amount_out - fee,
+            fee,
+        }
+    );
+    client
+        .exchange(
+            &setup.admin,
+            &booth,
+            &token_account_a,
+            &token_account_b,
+            mode,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(client, &token_account_a).await,
+        (100 - 40) * base_a - quote.amount_in
+    );
+    assert_eq!(
+        token_balance(client, &token_account_b).await,
+        (100 - 50) * base_b + quote.amount_out
+    );
+    let booth = client.booth_at(&booth.address).await.unwrap();
+    assert_eq!(booth.booth.fees_b, quote.fee);
+    assert_eq!(booth.available_b(), booth.balance_b - quote.fee);
+
+    // * TEST: the quote of an exact out exchange B for A is what the program transfers
+    let mode = ExchangeMode::ExactOut {
+        amount_out: 3 * base_a,
+        max_amount_in: u64::MAX,
+    };
+    let quote = booth.quote(&setup.mint_b, mode).unwrap();
+    let balance_b = token_balance(client, &token_account_b).await;
+    client
+        .exchange(
+            &setup.admin,
+            &booth,
+            &token_account_b,
+            &token_account_a,
+            mode,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(client, &token_account_b).await,
+        balance_b - quote.amount_in
+    );
+    let booth = client.booth_at(&booth.address).await.unwrap();
+    assert_eq!(booth.booth.fees_a, quote.fee);
+
+    // * TEST: quotes apply the same checks as the program
+    let too_large = ExchangeMode::ExactOut {
+        amount_out: booth.available_b() + 1,
+        max_amount_in: u64::MAX,
+    };
+    assert_client_error(
+        booth.quote(&setup.mint_a, too_large),
+        XBoothError::InsufficientLiquidity,
+    );
+    assert_client_error(
+        booth.quote(&Pubkey::new_unique(), too_large),
+        XBoothError::InvalidMint,
+    );
+    client.pause(&setup.admin, &booth).await.unwrap();
+    let booth = client.booth_at(&booth.address).await.unwrap();
+    assert_client_error(
+        booth.quote(
+            &setup.mint_a,
+            ExchangeMode::ExactIn {
+                amount_in: base_a,
+                min_amount_out: 0,
+            },
+        ),
+        XBoothError::BoothPaused,
+    );
+
+    // * TEST: program errors are returned by the client
+    let result = client
+        .exchange(
+            &setup.admin,
+            &booth,
+            &token_account_a,
+            &token_account_b,
+            ExchangeMode::ExactIn {
+                amount_in: base_a,
+                min_amount_out: 0,
+            },
+            None,
+        )
+        .await;
+    assert!(matches!(result, Err(ClientError::Transport(_))));
+
+    // * Hand the booth over to a new admin
+    let new_admin = Keypair::new();
+    client
+        .send(
+            &[system_instruction::transfer(
+                &setup.admin.pubkey(),
+                &new_admin.pubkey(),
+                1_000_000_000,
+            )],
+            &setup.admin,
+            &[],
+        )
+        .await
+        .unwrap();
+    client
+        .propose_admin(&setup.admin, &booth, &new_admin.pubkey())
+        .await
+        .unwrap();
+    client.accept_admin(&new_admin, &booth).await.unwrap();
+    let booth = client.booth_at(&booth.address).await.unwrap();
+    assert_eq!(booth.booth.admin, new_admin.pubkey());
+
+    // * TEST: the new admin withdraws and closes the booth
+    client
+        .withdraw(
+            &new_admin,
+            &booth,
+            &token_account_a,
+            &setup.mint_a,
+            10 * base_a,
+        )
+        .await
+        .unwrap();
+    let booth = client.booth_at(&booth.address).await.unwrap();
+    // the fee of the exact out exchange stays in vault A
+    assert_eq!(booth.balance_a, (40 + 4 - 3 - 10) * base_a + 3);
+    client
+        .close_exchange_booth(&new_admin, &booth, &token_account_a, &token_account_b)
+        .await
+        .unwrap();
+    assert!(client
+        .rpc
+        .get_account(&booth.address)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_client_quotes_oracle_price() {
+    let mut setup = setup_client(6, 6, 0).await;
+    let base = 10u64.pow(6);
+    let admin = setup.admin.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+    let client = &mut setup.client;
+    let (oracle, _oracle_bump) =
+        find_oracle_address(&client.program_id, &admin, &setup.mint_a, &setup.mint_b);
+
+    // * Price one token A at 2.5 tokens B
+    let booth = client
+        .booth(&admin, &setup.mint_a, &setup.mint_b)
+        .await
+        .unwrap();
+    client
+        .deposit(
+            &setup.admin,
+            &booth,
+            &token_account_b,
+            &setup.mint_b,
+            50 * base,
+        )
+        .await
+        .unwrap();
+    client
+        .update_oracle_price(&setup.admin, &setup.mint_a, &setup.mint_b, 25, -1, 0)
+        .await
+        .unwrap();
+    client
+        .set_oracle(&setup.admin, &booth, &oracle, 10, 100)
+        .await
+        .unwrap();
+
+    // * TEST: the quote uses the oracle price instead of the stored rate
+    let booth: BoothView = client.booth_at(&booth.address).await.unwrap();
+    assert_eq!(booth.rate().unwrap(), (25, 10));
+    let mode = ExchangeMode::ExactIn {
+        amount_in: 4 * base,
+        min_amount_out: 0,
+    };
+    let quote = booth.quote(&setup.mint_a, mode).unwrap();
+    assert_eq!(quote.amount_out, 10 * base);
+    client
+        .exchange(
+            &setup.admin,
+            &booth,
+            &token_account_a,
+            &token_account_b,
+            mode,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(client, &token_account_b).await,
+        (100 - 50) * base + quote.amount_out
+    );
+}
+
+/// check_quote_matches_exchange sends an exchange of up to one token and
+/// checks that the program transferred exactly the quoted amounts, or failed
+/// with the same error as the quote
+#[allow(clippy::too_many_arguments)]
+async fn check_quote_matches_exchange(
+    decimals_a: u8,
+    decimals_b: u8,
+    fee_bps: u16,
+    rate_numerator: u64,
+    rate_denominator: u64,
+    is_a_to_b: bool,
+    is_exact_out: bool,
+    amount_seed: u64,
+) {
+    let mut setup = setup_client(decimals_a, decimals_b, fee_bps).await;
+    let admin = setup.admin.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+    let client = &mut setup.client;
+
+    let booth = client
+        .booth(&admin, &setup.mint_a, &setup.mint_b)
+        .await
+        .unwrap();
+    client
+        .set_exchange_rate(&setup.admin, &booth, rate_numerator, rate_denominator)
+        .await
+        .unwrap();
+    for (token_account, mint, decimals) in [
+        (&token_account_a, &setup.mint_a, decimals_a),
+        (&token_account_b, &setup.mint_b, decimals_b),
+    ] {
+        client
+            .deposit(
+                &setup.admin,
+                &booth,
+                token_account,
+                mint,
+                50 * 10u64.pow(decimals.into()),
+            )
+            .await
+            .unwrap();
+    }
+
+    let (from, receiving, mint_in, decimals_in, decimals_out) = if is_a_to_b {
+        (
+            token_account_a,
+            token_account_b,
+            setup.mint_a,
+            decimals_a,
+            decimals_b,
+        )
+    } else {
+        (
+            token_account_b,
+            token_account_a,
+            setup.mint_b,
+            decimals_b,
+            decimals_a,
+        )
+    };
+    let mode = if is_exact_out {
+        ExchangeMode::ExactOut {
+            amount_out: 1 + amount_seed % 10u64.pow(decimals_out.into()),
+            max_amount_in: u64::MAX,
+        }
+    } else {
+        ExchangeMode::ExactIn {
+            amount_in: 1 + amount_seed % 10u64.pow(decimals_in.into()),
+            min_amount_out: 0,
+        }
+    };
+
+    let booth = client.booth_at(&booth.address).await.unwrap();
+    let quote = booth.quote(&mint_in, mode);
+    let from_balance = token_balance(client, &from).await;
+    let receiving_balance = token_balance(client, &receiving).await;
+    let result = client
+        .exchange(&setup.admin, &booth, &from, &receiving, mode, None)
+        .await;
+
+    match quote {
+        Ok(quote) => {
+            result.unwrap();
+            assert_eq!(
+                from_balance - token_balance(client, &from).await,
+                quote.amount_in
+            );
+            assert_eq!(
+                token_balance(client, &receiving).await - receiving_balance,
+                quote.amount_out
+            );
+            let booth_after = client.booth_at(&booth.address).await.unwrap();
+            let fees_out = if is_a_to_b {
+                booth_after.booth.fees_b - booth.booth.fees_b
+            } else {
+                booth_after.booth.fees_a - booth.booth.fees_a
+            };
+            assert_eq!(fees_out, quote.fee);
+        }
+        Err(error) => {
+            let code = program_error_code(&error);
+            assert!(code.is_some(), "unexpected quote error {:?}", error);
+            assert_eq!(program_error_code(&result.unwrap_err()), code);
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    // * TEST: the off-chain quote equals the tokens moved by the program
+    #[test]
+    fn test_quote_matches_exchange(
+        decimals_a in 0u8..=9,
+        decimals_b in 0u8..=9,
+        fee_bps in 0u16..=1_000,
+        rate_numerator in 1u64..=20,
+        rate_denominator in 1u64..=20,
+        is_a_to_b in any::<bool>(),
+        is_exact_out in any::<bool>(),
+        amount_seed in any::<u64>(),
+    ) {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(check_quote_matches_exchange(
+                decimals_a,
+                decimals_b,
+                fee_bps,
+                rate_numerator,
+                rate_denominator,
+                is_a_to_b,
+                is_exact_out,
+                amount_seed,
+            ));
+    }
+}
@@ -0,0 +1,126 @@
+use borsh::BorshSerialize;
+use solana_program::pubkey::Pubkey;
+use xbooth::events::{parse_events, XBoothEvent};
+
+/// program_data_log formats the data the way the runtime logs sol_log_data
+fn program_data_log(data: &[&[u8]]) -> String {
+    let fields: Vec<String> = data.iter().map(base64::encode).collect();
+    format!("Program data: {}", fields.join(" "))
+}
+
+fn event_log(event: &XBoothEvent) -> String {
+    program_data_log(&[&event.try_to_vec().unwrap()])
+}
+
+#[test]
+fn test_parse_events() {
+    let program_id = Pubkey::new_unique();
+    let other_program_id = Pubkey::new_unique();
+    let booth = Pubkey::new_unique();
+    let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (vault_a, vault_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let admin = Pubkey::new_unique();
+    let token_account = Pubkey::new_unique();
+
+    let initialized = XBoothEvent::BoothInitialized {
+        booth,
+        admin,
+        mint_a,
+        mint_b,
+        vault_a,
+        vault_b,
+        rate_numerator: 3,
+        rate_denominator: 2,
+        fee_bps: 25,
+    };
+    let deposited = XBoothEvent::Deposited {
+        booth,
+        mint: mint_a,
+        vault: vault_a,
+        token_account,
+        amount: 40_000_000,
+    };
+    let withdrawn = XBoothEvent::Withdrawn {
+        booth,
+        mint: mint_b,
+        vault: vault_b,
+        token_account,
+        amount: u64::MAX,
+    };
+    let exchanged = XBoothEvent::Exchanged {
+        booth,
+        trader: Pubkey::new_unique(),
+        mint_in: mint_a,
+        mint_out: mint_b,
+        amount_in: 10_000_000,
+        amount_out: 14_962_500,
+        fee: 37_500,
+    };
+    let rate_changed = XBoothEvent::RateChanged {
+        booth,
+        rate_numerator: 1,
+        rate_denominator: 1,
+    };
+    let fees_withdrawn = XBoothEvent::FeesWithdrawn {
+        booth,
+        admin,
+        amount_a: 0,
+        amount_b: 37_500,
+    };
+    let closed = XBoothEvent::Closed {
+        booth,
+        admin,
+        amount_a: 50_000_000,
+        amount_b: 0,
+    };
+
+    let logs = vec![
+        format!("Program {} invoke [1]", program_id),
+        "Program log: xbooth initialize exchange booth".to_string(),
+        event_log(&initialized),
+        format!(
+            "Program {} consumed 1000 of 200000 compute units",
+            program_id
+        ),
+        format!("Program {} success", program_id),
+        // * the xbooth program is invoked again in the same transaction
+        format!("Program {} invoke [1]", program_id),
+        "Program log: xbooth deposit".to_string(),
+        format!("Program {} invoke [2]", other_program_id),
+        "Program log: invoke".to_string(),
+        // * data of the invoked program is not an xbooth event
+        event_log(&withdrawn),
+        format!("Program {} success", other_program_id),
+        event_log(&deposited),
+        // * data that is not an event is skipped
+        program_data_log(&[b"not an event"]),
+        format!("Program {} success", program_id),
+        // * events of a failed invocation are still decoded
+        format!("Program {} invoke [1]", program_id),
+        program_data_log(&[
+            &exchanged.try_to_vec().unwrap(),
+            &rate_changed.try_to_vec().unwrap(),
+        ]),
+        format!("Program {} failed: custom program error: 0x1", program_id),
+        // * data logged outside of an invocation is skipped
+        event_log(&closed),
+        format!("Program {} invoke [1]", program_id),
+        event_log(&fees_withdrawn),
+        event_log(&closed),
+        format!("Program {} success", program_id),
+    ];
+
+    assert_eq!(
+        parse_events(&program_id, &logs),
+        vec![
+            initialized,
+            deposited,
+            exchanged,
+            rate_changed,
+            fees_withdrawn,
+            closed
+        ]
+    );
+    assert!(parse_events(&other_program_id, &logs).contains(&withdrawn));
+    assert!(parse_events(&Pubkey::new_unique(), &logs).is_empty());
+}
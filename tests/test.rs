@@ -1,19 +1,38 @@
 //#![cfg(feature = "test-bpf")]
+use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     clock::BankId,
     instruction::{self, Instruction},
     program_error::ProgramError,
     pubkey::Pubkey,
     system_program,
-    sysvar::{self, recent_blockhashes},
+    sysvar::recent_blockhashes,
 };
 use solana_program_test::*;
 use solana_sdk::{
-    hash::Hash, program_pack::Pack, signature::Keypair, signer::Signer, system_instruction,
-    transaction::Transaction, transport::TransportError,
+    hash::Hash,
+    instruction::InstructionError,
+    program_pack::Pack,
+    signature::Keypair,
+    signer::Signer,
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+    transport::TransportError,
 };
 use spl_token::state::{Account, Mint};
-use std::mem;
+use xbooth::errors::XBoothError;
+use xbooth::events::{parse_events, XBoothEvent};
+use xbooth::instruction::{
+    accept_admin, close_exchange_booth, deposit, exchange, exchange_exact_out,
+    initialize_exchange_booth, migrate_booth, pause, propose_admin, set_exchange_rate, set_fee,
+    set_min_reserves, set_oracle, unpause, update_oracle_price, withdraw, withdraw_fees,
+    XBoothIntruction,
+};
+use xbooth::processor::utils::{canonical_mints, find_exchange_booth_address, find_vault_address};
+use xbooth::state::{
+    ExchangeBoothAccount, ExchangeBoothAccountV1, EXCHANGE_BOOTH_ACCOUNT_LEN,
+    EXCHANGE_BOOTH_ACCOUNT_V1_LEN, EXCHANGE_BOOTH_ACCOUNT_VERSION,
+};
 
 /// create_and_initialize_account sets up a new account and
 
@@ -139,46 +158,378 @@ async fn create_and_initialize_mint(
     Ok(())
 }
 
-fn create_exchange_booth_pda(
-    program_id: &Pubkey,
-    authority: &Keypair,
-    mint_a: &Keypair,
-    mint_b: &Keypair,
-) -> (Pubkey, u8) {
-    Pubkey::find_program_address(
-        &[
-            b"xbooth",
-            authority.pubkey().as_ref(),
-            mint_a.pubkey().as_ref(),
-            mint_b.pubkey().as_ref(),
-        ],
+/// new_mint_keypairs returns the keypairs of mint A and mint B of a new
+/// exchange booth in canonical order
+fn new_mint_keypairs() -> (Keypair, Keypair) {
+    let (mint_x, mint_y) = (Keypair::new(), Keypair::new());
+    if mint_x.pubkey() < mint_y.pubkey() {
+        (mint_x, mint_y)
+    } else {
+        (mint_y, mint_x)
+    }
+}
+
+/// ExchangeBoothSetup holds an initialized exchange booth together with
+/// a token account for each mint owned by the booth admin
+struct ExchangeBoothSetup {
+    context: ProgramTestContext,
+    banks_client: BanksClient,
+    recent_blockhash: Hash,
+    program_id: Pubkey,
+    authority: Keypair,
+    mint_authority: Keypair,
+    mint_a: Keypair,
+    mint_b: Keypair,
+    token_account_a: Keypair,
+    token_account_b: Keypair,
+    xbooth_pda: Pubkey,
+    vault_a_pda: Pubkey,
+    vault_b_pda: Pubkey,
+}
+
+/// setup_exchange_booth creates the mints, funds a token account for each
+/// mint with initial_amount and initializes an exchange booth for the pair
+/// with the exchange rate rate_numerator/rate_denominator and fee_bps
+async fn setup_exchange_booth(
+    mint_a_decimals: u8,
+    mint_b_decimals: u8,
+    initial_amount: f64,
+    rate_numerator: u64,
+    rate_denominator: u64,
+    fee_bps: u16,
+) -> ExchangeBoothSetup {
+    let program_id = Pubkey::new_unique();
+    let (mint_a, mint_b) = new_mint_keypairs();
+    let mint_authority = Keypair::new();
+
+    let mut program_test = ProgramTest::new("xbooth", program_id, None);
+    program_test.add_account(
+        mint_authority.pubkey(),
+        solana_sdk::account::Account {
+            lamports: 100_000_000_000,
+            data: vec![],
+            owner: system_program::id(),
+            ..solana_sdk::account::Account::default()
+        },
+    );
+    let context = program_test.start_with_context().await;
+    let mut banks_client = context.banks_client.clone();
+    let authority = Keypair::from_bytes(&context.payer.to_bytes()).unwrap();
+    let recent_blockhash = context.last_blockhash;
+
+    for (mint, decimals) in [(&mint_a, &mint_a_decimals), (&mint_b, &mint_b_decimals)] {
+        create_and_initialize_mint(
+            &mut banks_client,
+            recent_blockhash,
+            &mint_authority,
+            &mint_authority,
+            mint,
+            &spl_token::id(),
+            decimals,
+        )
+        .await
+        .unwrap();
+    }
+
+    let token_account_a = Keypair::new();
+    let token_account_b = Keypair::new();
+    for (token_account, mint, decimals) in [
+        (&token_account_a, &mint_a, mint_a_decimals),
+        (&token_account_b, &mint_b, mint_b_decimals),
+    ] {
+        create_and_initialize_account_for_mint(
+            &mut banks_client,
+            recent_blockhash,
+            &spl_token::id(),
+            token_account,
+            mint,
+            &authority,
+        )
+        .await
+        .unwrap();
+
+        mint_amount(
+            &mut banks_client,
+            recent_blockhash,
+            &spl_token::id(),
+            &token_account.pubkey(),
+            &mint.pubkey(),
+            &mint_authority,
+            &authority,
+            initial_amount,
+            decimals,
+        )
+        .await
+        .unwrap();
+    }
+
+    let (xbooth_pda, _xbooth_bump_seed, _, _) = find_exchange_booth_address(
+        &program_id,
+        &authority.pubkey(),
+        &mint_a.pubkey(),
+        &mint_b.pubkey(),
+    );
+    let (vault_a_pda, _vault_a_bump) = find_vault_address(
+        &program_id,
+        &authority.pubkey(),
+        &mint_a.pubkey(),
+        &xbooth_pda,
+    );
+    let (vault_b_pda, _vault_b_bump) = find_vault_address(
+        &program_id,
+        &authority.pubkey(),
+        &mint_b.pubkey(),
+        &xbooth_pda,
+    );
+
+    let initialize_ix = initialize_exchange_booth(
+        &program_id,
+        &authority.pubkey(),
+        &mint_a.pubkey(),
+        &mint_b.pubkey(),
+        rate_numerator,
+        rate_denominator,
+        fee_bps,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[initialize_ix],
+        Some(&authority.pubkey()),
+        &[&authority],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    ExchangeBoothSetup {
+        context,
+        banks_client,
+        recent_blockhash,
         program_id,
+        authority,
+        mint_authority,
+        mint_a,
+        mint_b,
+        token_account_a,
+        token_account_b,
+        xbooth_pda,
+        vault_a_pda,
+        vault_b_pda,
+    }
+}
+
+/// deposit_ix deposits amount from the admin token account into the vault of mint
+fn deposit_ix(
+    setup: &ExchangeBoothSetup,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    deposit(
+        &setup.program_id,
+        &setup.authority.pubkey(),
+        &setup.authority.pubkey(),
+        token_account,
+        mint,
+        &other_mint(setup, mint),
+        amount,
     )
 }
 
-fn create_vault_pda(
-    program_id: &Pubkey,
-    authority: &Keypair,
-    mint_account: &Keypair,
-    owner: &Pubkey,
-) -> (Pubkey, u8) {
-    Pubkey::find_program_address(
-        &[
-            b"xbooth",
-            authority.pubkey().as_ref(),
-            mint_account.pubkey().as_ref(),
-            owner.as_ref(),
-        ],
-        program_id,
+/// withdraw_ix withdraws amount from the vault of mint into the admin token account
+fn withdraw_ix(
+    setup: &ExchangeBoothSetup,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    withdraw(
+        &setup.program_id,
+        &setup.authority.pubkey(),
+        &setup.authority.pubkey(),
+        token_account,
+        mint,
+        &other_mint(setup, mint),
+        amount,
+    )
+}
+
+/// withdraw_fees_ix withdraws the collected fees into the token accounts of the setup
+fn withdraw_fees_ix(setup: &ExchangeBoothSetup, admin: &Pubkey) -> Instruction {
+    withdraw_fees(
+        &setup.program_id,
+        &setup.authority.pubkey(),
+        admin,
+        &setup.token_account_a.pubkey(),
+        &setup.token_account_b.pubkey(),
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+    )
+}
+
+/// other_mint returns the mint of the exchange booth that is not mint
+fn other_mint(setup: &ExchangeBoothSetup, mint: &Pubkey) -> Pubkey {
+    if *mint == setup.mint_a.pubkey() {
+        setup.mint_b.pubkey()
+    } else {
+        setup.mint_a.pubkey()
+    }
+}
+
+/// exchange_ix exchanges amount of tokens from the from_token_account into
+/// the other token received in the receiving_token_account
+fn exchange_ix(
+    setup: &ExchangeBoothSetup,
+    from_token_account: &Pubkey,
+    receiving_token_account: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    exchange(
+        &setup.program_id,
+        &setup.authority.pubkey(),
+        &setup.authority.pubkey(),
+        from_token_account,
+        receiving_token_account,
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+        amount,
+        0,
+        None,
+    )
+}
+
+/// set_oracle_ix lets the exchange booth price exchanges with the oracle
+fn set_oracle_ix(
+    setup: &ExchangeBoothSetup,
+    oracle: Pubkey,
+    max_staleness: u64,
+    max_confidence_bps: u16,
+) -> Instruction {
+    set_oracle(
+        &setup.program_id,
+        &setup.authority.pubkey(),
+        &setup.authority.pubkey(),
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+        &oracle,
+        max_staleness,
+        max_confidence_bps,
+    )
+}
+
+/// update_oracle_price_ix publishes a price to the oracle of the booth admin
+fn update_oracle_price_ix(
+    setup: &ExchangeBoothSetup,
+    price: u64,
+    expo: i32,
+    conf: u64,
+) -> Instruction {
+    update_oracle_price(
+        &setup.program_id,
+        &setup.authority.pubkey(),
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+        price,
+        expo,
+        conf,
+    )
+}
+
+/// token_balance returns the raw amount held by a token account
+async fn token_balance(banks_client: &mut BanksClient, token_account: &Pubkey) -> u64 {
+    let account = banks_client
+        .get_account(*token_account)
+        .await
+        .unwrap()
+        .expect("could not fetch token account");
+    Account::unpack(&account.data).unwrap().amount
+}
+
+/// assert_transaction_error processes the transaction and checks that it
+/// failed in the instruction at instruction_index with error
+async fn assert_transaction_error(
+    banks_client: &mut BanksClient,
+    tx: Transaction,
+    instruction_index: u8,
+    error: InstructionError,
+) {
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(instruction_index, error)
+    );
+}
+
+/// assert_xbooth_error checks that the transaction failed with the custom
+/// error code of the XBoothError
+async fn assert_xbooth_error(
+    banks_client: &mut BanksClient,
+    tx: Transaction,
+    instruction_index: u8,
+    error: XBoothError,
+) {
+    assert_transaction_error(
+        banks_client,
+        tx,
+        instruction_index,
+        InstructionError::Custom(error as u32),
     )
+    .await;
+}
+
+/// simulate returns the compute units consumed by the instructions and the
+/// log messages of the transaction. The banks server only returns the
+/// simulation details of a failed transaction, so an instruction that
+/// always fails is appended
+async fn simulate(
+    setup: &mut ExchangeBoothSetup,
+    instructions: &[Instruction],
+) -> (u64, Vec<String>) {
+    let failing_ix = Instruction {
+        program_id: setup.program_id,
+        accounts: vec![],
+        data: vec![0],
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[instructions, &[failing_ix]].concat(),
+        Some(&setup.authority.pubkey()),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    let result = setup
+        .banks_client
+        .process_transaction_with_preflight_and_commitment_and_context(
+            tarpc::context::current(),
+            tx,
+            solana_sdk::commitment_config::CommitmentLevel::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        result.result.unwrap().unwrap_err(),
+        TransactionError::InstructionError(
+            instructions.len() as u8,
+            InstructionError::Custom(XBoothError::UnsupportedInstructionVersion as u32)
+        )
+    );
+    let simulation_details = result.simulation_details.unwrap();
+    (simulation_details.units_consumed, simulation_details.logs)
+}
+
+/// units_consumed simulates the instructions and returns the compute units
+/// they consume
+async fn units_consumed(setup: &mut ExchangeBoothSetup, instructions: &[Instruction]) -> u64 {
+    let (units, _logs) = simulate(setup, instructions).await;
+    units
 }
 
 #[tokio::test]
 async fn test_deposit_into_vault() {
     // Initialize program test
     let program_id = Pubkey::new_unique();
-    let mint_a = Keypair::new();
-    let mint_b = Keypair::new();
+    let (mint_a, mint_b) = new_mint_keypairs();
     let auth = Keypair::new();
 
     let mut program_test = ProgramTest::new("xbooth", program_id, None);
@@ -221,28 +572,6 @@ async fn test_deposit_into_vault() {
     .unwrap();
     // ------------------------------
 
-    // * Create Accounts to hold A token and B token
-    let system_program_account = instruction::AccountMeta {
-        pubkey: system_program::id(),
-        is_signer: false,
-        is_writable: false,
-    };
-
-    let (xbooth_pda, _xbooth_bump_seed) =
-        create_exchange_booth_pda(&program_id, &authority, &mint_a, &mint_b);
-
-    let exchange_booth_account = instruction::AccountMeta {
-        pubkey: xbooth_pda,
-        is_signer: false,
-        is_writable: true,
-    };
-
-    let authority_account = instruction::AccountMeta {
-        pubkey: authority.pubkey(),
-        is_signer: true,
-        is_writable: false,
-    };
-
     // * Create token account for A tokens
     let token_account = Keypair::new();
     create_and_initialize_account_for_mint(
@@ -300,104 +629,30 @@ async fn test_deposit_into_vault() {
     .await
     .unwrap();
 
-    // * Create accounts needed for the instructions
-    let token_account_a_meta = instruction::AccountMeta {
-        pubkey: token_account.pubkey(),
-        is_signer: false,
-        is_writable: true,
-    };
-
-    let token_account_b_meta = instruction::AccountMeta {
-        pubkey: token_account_b.pubkey(),
-        is_signer: false,
-        is_writable: true,
-    };
-    // initialize authority account to hold same token as in vault
-
-    let (vault_a_pda, _vault_a_bump) =
-        create_vault_pda(&program_id, &authority, &mint_a, &xbooth_pda);
-
-    let vault_a_account = instruction::AccountMeta {
-        pubkey: vault_a_pda,
-        is_signer: false,
-        is_writable: true,
-    };
-
-    let (vault_b_pda, _vault_b_bump) =
-        create_vault_pda(&program_id, &authority, &mint_b, &xbooth_pda);
-
-    let vault_b_account = instruction::AccountMeta {
-        pubkey: vault_b_pda,
-        is_signer: false,
-        is_writable: true,
-    };
-
-    let mint_a_account = instruction::AccountMeta {
-        pubkey: mint_a.pubkey(),
-        is_signer: false,
-        is_writable: false,
-    };
-
-    let mint_b_account = instruction::AccountMeta {
-        pubkey: mint_b.pubkey(),
-        is_signer: false,
-        is_writable: false,
-    };
-
-    let token_program_account = instruction::AccountMeta {
-        pubkey: spl_token::id(),
-        is_signer: false,
-        is_writable: false,
-    };
-
-    let rent_account = instruction::AccountMeta {
-        pubkey: solana_program::sysvar::rent::id(),
-        is_signer: false,
-        is_writable: false,
-    };
-
     // * ---------- Instructions and Transactions ---------------
 
     // * 1. Initialize exchange booth, instruction
-    let initialize_accounts = vec![
-        exchange_booth_account.clone(),
-        authority_account.clone(),
-        system_program_account.clone(),
-        vault_a_account.clone(),
-        vault_b_account.clone(),
-        mint_a_account.clone(),
-        mint_b_account.clone(),
-        token_program_account.clone(),
-        rent_account.clone(),
-    ];
-
-    let initialize_exchange_booth_data = vec![0; mem::size_of::<u8>()];
-    let intiialize_ix = instruction::Instruction {
-        program_id: program_id,
-        accounts: initialize_accounts,
-        data: initialize_exchange_booth_data,
-    };
+    let intiialize_ix = initialize_exchange_booth(
+        &program_id,
+        &authority.pubkey(),
+        &mint_a.pubkey(),
+        &mint_b.pubkey(),
+        1,
+        2,
+        0,
+    );
 
     // * Deposit tokens into vault A, instruction
-    let deposit_accounts = vec![
-        exchange_booth_account.clone(),
-        authority_account.clone(),
-        token_account_a_meta.clone(),
-        vault_a_account.clone(),
-        mint_a_account.clone(),
-        mint_b_account.clone(),
-        token_program_account.clone(),
-    ];
-
-    let deposit_amount: f64 = 50.0;
-    let deposit_instruction: Vec<u8> = vec![1; mem::size_of::<u8>()];
-    let transfer_amount = deposit_amount.to_le_bytes().to_vec();
-    let deposit_input_data = [&deposit_instruction[..], &transfer_amount[..]].concat();
-    let deposit_ix = instruction::Instruction {
-        program_id,
-        accounts: deposit_accounts.clone(),
-        data: deposit_input_data.clone(),
-    };
+    let deposit_amount: u64 = 50 * 10u64.pow(mint_a_decimals.into());
+    let deposit_ix = deposit(
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &token_account.pubkey(),
+        &mint_a.pubkey(),
+        &mint_b.pubkey(),
+        deposit_amount,
+    );
 
     // * Process transaction
     let tx = Transaction::new_signed_with_payer(
@@ -418,21 +673,15 @@ async fn test_deposit_into_vault() {
     println!("token account data: {:?}", account_data);
 
     // * Deposit tokens into vault B, instruction
-    let deposit_b_accounts = vec![
-        exchange_booth_account.clone(),
-        authority_account.clone(),
-        token_account_b_meta.clone(),
-        vault_b_account.clone(),
-        mint_a_account.clone(),
-        mint_b_account.clone(),
-        token_program_account.clone(),
-    ];
-
-    let deposit_b_account_ix = instruction::Instruction {
-        program_id,
-        accounts: deposit_b_accounts.clone(),
-        data: deposit_input_data.clone(),
-    };
+    let deposit_b_account_ix = deposit(
+        &program_id,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        &token_account_b.pubkey(),
+        &mint_b.pubkey(),
+        &mint_a.pubkey(),
+        deposit_amount,
+    );
 
     let tx = Transaction::new_signed_with_payer(
         &[deposit_b_account_ix],
@@ -520,3 +769,2123 @@ async fn test_deposit_into_vault() {
     //     .await
     //     .unwrap();
 }
+
+#[tokio::test]
+async fn test_close_exchange_booth() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 1, 1, 0).await;
+    let authority = setup.authority.pubkey();
+
+    // * Fill both vaults so there is something to drain
+    let deposit_a_ix = deposit_ix(
+        &setup,
+        &setup.token_account_a.pubkey(),
+        &setup.mint_a.pubkey(),
+        40 * base,
+    );
+    let deposit_b_ix = deposit_ix(
+        &setup,
+        &setup.token_account_b.pubkey(),
+        &setup.mint_b.pubkey(),
+        25 * base,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_a_ix, deposit_b_ix],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    let booth_rent = setup
+        .banks_client
+        .get_balance(setup.xbooth_pda)
+        .await
+        .unwrap();
+    let vault_rent = setup
+        .banks_client
+        .get_balance(setup.vault_a_pda)
+        .await
+        .unwrap()
+        + setup
+            .banks_client
+            .get_balance(setup.vault_b_pda)
+            .await
+            .unwrap();
+    let authority_balance = setup.banks_client.get_balance(authority).await.unwrap();
+
+    // * Close the exchange booth
+    let close_ix = close_exchange_booth(
+        &setup.program_id,
+        &authority,
+        &authority,
+        &setup.token_account_a.pubkey(),
+        &setup.token_account_b.pubkey(),
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+    );
+    // the mint authority pays the fee so the admin balance only reflects the returned rent
+    let tx = Transaction::new_signed_with_payer(
+        &[close_ix],
+        Some(&setup.mint_authority.pubkey()),
+        &[&setup.mint_authority, &setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: the vault balances are returned to the admin token accounts
+    let expected_amount = 100 * base;
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.token_account_a.pubkey()).await,
+        expected_amount
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.token_account_b.pubkey()).await,
+        expected_amount
+    );
+
+    // * TEST: the booth and the vaults are closed
+    for closed in [setup.xbooth_pda, setup.vault_a_pda, setup.vault_b_pda] {
+        assert!(
+            setup
+                .banks_client
+                .get_account(closed)
+                .await
+                .unwrap()
+                .is_none(),
+            "account {} should be closed",
+            closed
+        );
+    }
+
+    // * TEST: the rent is returned to the admin
+    assert_eq!(
+        setup.banks_client.get_balance(authority).await.unwrap(),
+        authority_balance + booth_rent + vault_rent
+    );
+}
+
+#[tokio::test]
+async fn test_exchange_with_stored_rate() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    // one token A buys two tokens B
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 2, 1, 0).await;
+    let authority = setup.authority.pubkey();
+
+    let deposit_b_ix = deposit_ix(
+        &setup,
+        &setup.token_account_b.pubkey(),
+        &setup.mint_b.pubkey(),
+        50 * base,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            deposit_b_ix,
+            exchange_ix(
+                &setup,
+                &setup.token_account_a.pubkey(),
+                &setup.token_account_b.pubkey(),
+                10 * base,
+            ),
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: 10 A is exchanged for 20 B
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.token_account_a.pubkey()).await,
+        90 * base
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.token_account_b.pubkey()).await,
+        (100 - 50 + 20) * base
+    );
+
+    // * Change the rate so one token A buys a quarter token B
+    let set_rate_ix = set_exchange_rate(
+        &setup.program_id,
+        &authority,
+        &authority,
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+        1,
+        4,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            set_rate_ix,
+            exchange_ix(
+                &setup,
+                &setup.token_account_a.pubkey(),
+                &setup.token_account_b.pubkey(),
+                8 * base,
+            ),
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: 8 A is exchanged for 2 B
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.token_account_a.pubkey()).await,
+        82 * base
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.token_account_b.pubkey()).await,
+        (100 - 50 + 20 + 2) * base
+    );
+}
+
+#[tokio::test]
+async fn test_exchange_with_oracle_price() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 1, 1, 0).await;
+    let authority = setup.authority.pubkey();
+
+    let (oracle_pda, _oracle_bump) = Pubkey::find_program_address(
+        &[
+            b"xbooth_oracle",
+            authority.as_ref(),
+            setup.mint_a.pubkey().as_ref(),
+            setup.mint_b.pubkey().as_ref(),
+        ],
+        &setup.program_id,
+    );
+
+    // * Price one token A at 2.5 +- 0.1 tokens B and trust the oracle
+    let deposit_b_ix = deposit_ix(
+        &setup,
+        &setup.token_account_b.pubkey(),
+        &setup.mint_b.pubkey(),
+        50 * base,
+    );
+    let mut oracle_exchange_ix = exchange_ix(
+        &setup,
+        &setup.token_account_a.pubkey(),
+        &setup.token_account_b.pubkey(),
+        4 * base,
+    );
+    oracle_exchange_ix
+        .accounts
+        .push(instruction::AccountMeta::new_readonly(oracle_pda, false));
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            deposit_b_ix,
+            update_oracle_price_ix(&setup, 25, -1, 1),
+            set_oracle_ix(&setup, oracle_pda, 10, 500),
+            oracle_exchange_ix.clone(),
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: 4 A is exchanged for 10 B at the oracle price
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.token_account_a.pubkey()).await,
+        96 * base
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.token_account_b.pubkey()).await,
+        (100 - 50 + 10) * base
+    );
+
+    // * TEST: the exchange is rejected without the oracle account
+    let tx = Transaction::new_signed_with_payer(
+        &[exchange_ix(
+            &setup,
+            &setup.token_account_a.pubkey(),
+            &setup.token_account_b.pubkey(),
+            4 * base,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_transaction_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        InstructionError::NotEnoughAccountKeys,
+    )
+    .await;
+
+    // * TEST: the exchange is rejected if the confidence interval is too wide
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            update_oracle_price_ix(&setup, 25, -1, 5),
+            oracle_exchange_ix.clone(),
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        1,
+        XBoothError::OracleConfidenceTooWide,
+    )
+    .await;
+
+    // * TEST: the exchange is rejected once the price is stale
+    let slot = setup.banks_client.get_root_slot().await.unwrap();
+    setup.context.warp_to_slot(slot + 20).unwrap();
+    let recent_blockhash = setup.banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[oracle_exchange_ix.clone()],
+        Some(&authority),
+        &[&setup.authority],
+        recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::StaleOraclePrice,
+    )
+    .await;
+
+    // * TEST: a fresh price makes the oracle usable again
+    let tx = Transaction::new_signed_with_payer(
+        &[update_oracle_price_ix(&setup, 5, 0, 0), oracle_exchange_ix],
+        Some(&authority),
+        &[&setup.authority],
+        recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.token_account_b.pubkey()).await,
+        (100 - 50 + 10 + 20) * base
+    );
+}
+
+#[tokio::test]
+async fn test_exchange_b_for_a() {
+    let mint_a_decimals = 6;
+    let mint_b_decimals = 9;
+    // one token A buys four tokens B
+    let mut setup = setup_exchange_booth(mint_a_decimals, mint_b_decimals, 100.0, 4, 1, 0).await;
+    let authority = setup.authority.pubkey();
+
+    let deposit_a_ix = deposit_ix(
+        &setup,
+        &setup.token_account_a.pubkey(),
+        &setup.mint_a.pubkey(),
+        50 * 10u64.pow(mint_a_decimals.into()),
+    );
+    let exchange_b_for_a_ix = exchange_ix(
+        &setup,
+        &setup.token_account_b.pubkey(),
+        &setup.token_account_a.pubkey(),
+        20 * 10u64.pow(mint_b_decimals.into()),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_a_ix, exchange_b_for_a_ix],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: 20 B is exchanged for 5 A at the inverse rate
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.token_account_a.pubkey()).await,
+        (100 - 50 + 5) * 10u64.pow(mint_a_decimals.into())
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.token_account_b.pubkey()).await,
+        (100 - 20) * 10u64.pow(mint_b_decimals.into())
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_a_pda).await,
+        (50 - 5) * 10u64.pow(mint_a_decimals.into())
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_b_pda).await,
+        20 * 10u64.pow(mint_b_decimals.into())
+    );
+}
+
+#[tokio::test]
+async fn test_legacy_instruction_is_rejected() {
+    let mint_decimals = 6;
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 1, 1, 0).await;
+    let authority = setup.authority.pubkey();
+
+    // * Deposit encoded by a legacy client: tag followed by an f64 amount
+    let mut legacy_deposit_ix = deposit_ix(
+        &setup,
+        &setup.token_account_a.pubkey(),
+        &setup.mint_a.pubkey(),
+        0,
+    );
+    legacy_deposit_ix.data = [&[1u8][..], &40.0f64.to_le_bytes()[..]].concat();
+    let tx = Transaction::new_signed_with_payer(
+        &[legacy_deposit_ix],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+
+    // * TEST: the instruction is rejected with a clear error
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::UnsupportedInstructionVersion,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_exchange_fees() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    // one token A buys two tokens B, with a fee of 1%
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 2, 1, 100).await;
+    let authority = setup.authority.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+
+    // * Exchange 10 A for B and then 4 B for A
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            deposit_ix(&setup, &token_account_b, &setup.mint_b.pubkey(), 50 * base),
+            exchange_ix(&setup, &token_account_a, &token_account_b, 10 * base),
+            exchange_ix(&setup, &token_account_b, &token_account_a, 4 * base),
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: the fee is charged on the output and left in the vaults
+    // 10 A -> 20 B - 0.2 B fee, 4 B -> 2 A - 0.02 A fee
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_a).await,
+        100 * base - 10 * base + 2 * base - base / 50
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_b).await,
+        100 * base - 50 * base + 20 * base - base / 5 - 4 * base
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_a_pda).await,
+        8 * base + base / 50
+    );
+
+    // * TEST: the principal withdrawal cannot touch the fees
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix(
+            &setup,
+            &token_account_a,
+            &setup.mint_a.pubkey(),
+            8 * base + 1,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InsufficientFunds,
+    )
+    .await;
+
+    // * Withdraw the principal and then the fees
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            withdraw_ix(&setup, &token_account_a, &setup.mint_a.pubkey(), 8 * base),
+            withdraw_fees_ix(&setup, &authority),
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: the vault A is empty and the vault B only holds the principal
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_a_pda).await,
+        0
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_b_pda).await,
+        50 * base - 20 * base + 4 * base
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_a).await,
+        100 * base
+    );
+}
+
+#[tokio::test]
+async fn test_exchange_slippage_protection() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    // one token A buys two tokens B
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 2, 1, 0).await;
+    let authority = setup.authority.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix(
+            &setup,
+            &token_account_b,
+            &setup.mint_b.pubkey(),
+            50 * base,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    let slot = setup.banks_client.get_root_slot().await.unwrap();
+    let limited_exchange_ixs: Vec<Instruction> = [
+        (20 * base + 1, None),
+        (20 * base, Some(slot)),
+        (20 * base, Some(slot + 100)),
+    ]
+    .iter()
+    .map(|&(min_amount_out, expiry_slot)| {
+        exchange(
+            &setup.program_id,
+            &authority,
+            &authority,
+            &token_account_a,
+            &token_account_b,
+            &setup.mint_a.pubkey(),
+            &setup.mint_b.pubkey(),
+            10 * base,
+            min_amount_out,
+            expiry_slot,
+        )
+    })
+    .collect();
+
+    // * TEST: the exchange fails if the output is below the minimum
+    let tx = Transaction::new_signed_with_payer(
+        &[limited_exchange_ixs[0].clone()],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::SlippageExceeded,
+    )
+    .await;
+
+    // * TEST: the exchange fails after the expiry slot
+    let tx = Transaction::new_signed_with_payer(
+        &[limited_exchange_ixs[1].clone()],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.context.warp_to_slot(slot + 2).unwrap();
+    assert_xbooth_error(&mut setup.banks_client, tx, 0, XBoothError::TradeExpired).await;
+
+    // * TEST: the exchange succeeds within the limits
+    let tx = Transaction::new_signed_with_payer(
+        &[limited_exchange_ixs[2].clone()],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_b).await,
+        (100 - 50 + 20) * base
+    );
+}
+
+#[tokio::test]
+async fn test_exchange_exact_out() {
+    // one token A buys two tokens B, with a fee of 0.3%
+    let mut setup = setup_exchange_booth(6, 9, 100.0, 2, 1, 30).await;
+    let authority = setup.authority.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+    let base_a: u64 = 10u64.pow(6);
+    let base_b: u64 = 10u64.pow(9);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix(
+            &setup,
+            &token_account_b,
+            &setup.mint_b.pubkey(),
+            50 * base_b,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // receiving 10 B needs a gross output of ceil(10 B / 0.997) = 10.030090271 B,
+    // which costs ceil(10.030090271 / 2) = 5.015046 A
+    let amount_out = 10 * base_b;
+    let amount_in = 5_015_046;
+    let exact_out_ixs: Vec<Instruction> = [amount_in - 1, amount_in]
+        .iter()
+        .map(|&max_amount_in| {
+            exchange_exact_out(
+                &setup.program_id,
+                &authority,
+                &authority,
+                &token_account_a,
+                &token_account_b,
+                &setup.mint_a.pubkey(),
+                &setup.mint_b.pubkey(),
+                amount_out,
+                max_amount_in,
+                None,
+            )
+        })
+        .collect();
+
+    // * TEST: the exchange fails if the input is above the maximum
+    let tx = Transaction::new_signed_with_payer(
+        &[exact_out_ixs[0].clone()],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::SlippageExceeded,
+    )
+    .await;
+
+    // * TEST: the exact output is received for the rounded up input
+    let tx = Transaction::new_signed_with_payer(
+        &[exact_out_ixs[1].clone()],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_a).await,
+        100 * base_a - amount_in
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_b).await,
+        50 * base_b + amount_out
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_b_pda).await,
+        50 * base_b - amount_out
+    );
+}
+
+#[tokio::test]
+async fn test_exchange_insufficient_liquidity() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    // one token A buys two tokens B
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 2, 1, 0).await;
+    let authority = setup.authority.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+
+    // * Deposit 20 B and keep 5 B in reserve
+    let set_min_reserves_ix = set_min_reserves(
+        &setup.program_id,
+        &authority,
+        &authority,
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+        0,
+        5 * base,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            deposit_ix(&setup, &token_account_b, &setup.mint_b.pubkey(), 20 * base),
+            set_min_reserves_ix,
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: the exchange cannot dip into the reserve
+    let tx = Transaction::new_signed_with_payer(
+        &[exchange_ix(
+            &setup,
+            &token_account_a,
+            &token_account_b,
+            8 * base,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InsufficientLiquidity,
+    )
+    .await;
+
+    // * TEST: the exchange succeeds down to the reserve
+    let tx = Transaction::new_signed_with_payer(
+        &[exchange_ix(
+            &setup,
+            &token_account_a,
+            &token_account_b,
+            7 * base + base / 2,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_b_pda).await,
+        5 * base
+    );
+}
+
+#[tokio::test]
+async fn test_exchange_fee_keeps_reserve() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    // one token A buys two tokens B, with a fee of 1%
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 2, 1, 100).await;
+    let authority = setup.authority.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+
+    // * Deposit 20 B and keep 5 B in reserve
+    let set_min_reserves_ix = set_min_reserves(
+        &setup.program_id,
+        &authority,
+        &authority,
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+        0,
+        5 * base,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            deposit_ix(&setup, &token_account_b, &setup.mint_b.pubkey(), 20 * base),
+            set_min_reserves_ix,
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: the output after the fee fits the 15 B available, but the
+    // fee on top of it would come out of the reserve
+    // 7.55 A -> 15.1 B - 0.151 B fee
+    let tx = Transaction::new_signed_with_payer(
+        &[exchange_ix(
+            &setup,
+            &token_account_a,
+            &token_account_b,
+            7 * base + base * 55 / 100,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InsufficientLiquidity,
+    )
+    .await;
+
+    // * TEST: the exchange succeeds down to the reserve plus the fee
+    // 7.5 A -> 15 B - 0.15 B fee
+    let tx = Transaction::new_signed_with_payer(
+        &[exchange_ix(
+            &setup,
+            &token_account_a,
+            &token_account_b,
+            7 * base + base / 2,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_b_pda).await,
+        5 * base + 15 * base / 100
+    );
+
+    // * TEST: the fees are withdrawn and the reserve is left in the vault
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_fees_ix(&setup, &authority)],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_b_pda).await,
+        5 * base
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_b).await,
+        100 * base - 20 * base + 15 * base
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_accounts_return_error_codes() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 1, 1, 0).await;
+    let authority = setup.authority.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+
+    // * TEST: only the admin can deposit
+    let mut ix = deposit_ix(&setup, &token_account_a, &setup.mint_a.pubkey(), base);
+    ix.accounts[1] = instruction::AccountMeta::new_readonly(setup.mint_authority.pubkey(), true);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority),
+        &[&setup.authority, &setup.mint_authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(&mut setup.banks_client, tx, 0, XBoothError::InvalidOwner).await;
+
+    // * TEST: a deposit into the vault of the other mint is rejected
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix(
+            &setup,
+            &token_account_a,
+            &setup.mint_b.pubkey(),
+            base,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(&mut setup.banks_client, tx, 0, XBoothError::InvalidMint).await;
+
+    // * TEST: a deposit of more than the token account holds is rejected
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix(
+            &setup,
+            &token_account_a,
+            &setup.mint_a.pubkey(),
+            100 * base + 1,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InsufficientFunds,
+    )
+    .await;
+
+    // * TEST: a withdrawal from an account that is not the booth is rejected
+    let mut ix = withdraw_ix(&setup, &token_account_a, &setup.mint_a.pubkey(), base);
+    ix.accounts[0] = instruction::AccountMeta::new(setup.vault_b_pda, false);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InvalidAccountAddress,
+    )
+    .await;
+
+    // * TEST: a withdrawal from an account that is not the vault is rejected
+    let mut ix = withdraw_ix(&setup, &token_account_a, &setup.mint_a.pubkey(), base);
+    ix.accounts[3] = instruction::AccountMeta::new(token_account_b, false);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InvalidVaultAccount,
+    )
+    .await;
+
+    // * TEST: an exchange between token accounts of the same mint is rejected
+    let tx = Transaction::new_signed_with_payer(
+        &[exchange_ix(
+            &setup,
+            &token_account_a,
+            &token_account_a,
+            base,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::UniqueMintAccounts,
+    )
+    .await;
+
+    // * TEST: an exchange with an account that is not the booth is rejected
+    let mut ix = exchange_ix(&setup, &token_account_a, &token_account_b, base);
+    ix.accounts[0] = instruction::AccountMeta::new(Pubkey::new_unique(), false);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InvalidAccountAddress,
+    )
+    .await;
+
+    // * TEST: a fee of 100% or more is rejected
+    let set_fee_ix = set_fee(
+        &setup.program_id,
+        &authority,
+        &authority,
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+        10_000,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[set_fee_ix],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(&mut setup.banks_client, tx, 0, XBoothError::InvalidFee).await;
+}
+
+#[tokio::test]
+async fn test_exchange_by_any_trader() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    // one token A buys two tokens B
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 2, 1, 0).await;
+    let authority = setup.authority.pubkey();
+
+    // * Create token accounts for a trader that is not the admin
+    let trader = Keypair::new();
+    let trader_token_account_a = Keypair::new();
+    let trader_token_account_b = Keypair::new();
+    let tx = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &authority,
+            &trader.pubkey(),
+            1_000_000_000,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+    for (token_account, mint) in [
+        (&trader_token_account_a, &setup.mint_a),
+        (&trader_token_account_b, &setup.mint_b),
+    ] {
+        create_and_initialize_account_for_mint(
+            &mut setup.banks_client,
+            setup.recent_blockhash,
+            &spl_token::id(),
+            token_account,
+            mint,
+            &trader,
+        )
+        .await
+        .unwrap();
+    }
+    mint_amount(
+        &mut setup.banks_client,
+        setup.recent_blockhash,
+        &spl_token::id(),
+        &trader_token_account_a.pubkey(),
+        &setup.mint_a.pubkey(),
+        &setup.mint_authority,
+        &setup.authority,
+        10.0,
+        mint_decimals,
+    )
+    .await
+    .unwrap();
+
+    let mut trader_exchange_ix = exchange_ix(
+        &setup,
+        &trader_token_account_a.pubkey(),
+        &trader_token_account_b.pubkey(),
+        5 * base,
+    );
+    trader_exchange_ix.accounts[1] = instruction::AccountMeta::new_readonly(trader.pubkey(), true);
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            deposit_ix(
+                &setup,
+                &setup.token_account_b.pubkey(),
+                &setup.mint_b.pubkey(),
+                50 * base,
+            ),
+            trader_exchange_ix,
+        ],
+        Some(&authority),
+        &[&setup.authority, &trader],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: the trader exchanged 5 A for 10 B
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &trader_token_account_a.pubkey()).await,
+        5 * base
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &trader_token_account_b.pubkey()).await,
+        10 * base
+    );
+
+    // * TEST: the trader cannot route the exchange through its own accounts
+    let mut fake_vault_ix = exchange_ix(
+        &setup,
+        &trader_token_account_a.pubkey(),
+        &trader_token_account_b.pubkey(),
+        base,
+    );
+    fake_vault_ix.accounts[1] = instruction::AccountMeta::new_readonly(trader.pubkey(), true);
+    fake_vault_ix.accounts[4] =
+        instruction::AccountMeta::new(trader_token_account_a.pubkey(), false);
+    let tx = Transaction::new_signed_with_payer(
+        &[fake_vault_ix],
+        Some(&authority),
+        &[&setup.authority, &trader],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InvalidVaultAccount,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_withdraw_from_both_vaults() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    let mut setup = setup_exchange_booth(mint_decimals, 9, 100.0, 1, 1, 0).await;
+    let authority = setup.authority.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+    let base_b: u64 = 10u64.pow(9);
+
+    // * Deposit 10 A and 20 B, then withdraw 4 A and 15 B
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            deposit_ix(&setup, &token_account_a, &setup.mint_a.pubkey(), 10 * base),
+            deposit_ix(
+                &setup,
+                &token_account_b,
+                &setup.mint_b.pubkey(),
+                20 * base_b,
+            ),
+            withdraw_ix(&setup, &token_account_a, &setup.mint_a.pubkey(), 4 * base),
+            withdraw_ix(
+                &setup,
+                &token_account_b,
+                &setup.mint_b.pubkey(),
+                15 * base_b,
+            ),
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: both vaults were withdrawn from
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_a_pda).await,
+        6 * base
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_b_pda).await,
+        5 * base_b
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_b).await,
+        95 * base_b
+    );
+
+    // * TEST: the withdrawal from vault B is limited to its balance
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_ix(
+            &setup,
+            &token_account_b,
+            &setup.mint_b.pubkey(),
+            5 * base_b + 1,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InsufficientFunds,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_vaults_are_validated_against_booth_state() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 1, 1, 0).await;
+    let authority = setup.authority.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+
+    // * TEST: a deposit into a token account that is not a vault is rejected
+    let mut ix = deposit_ix(&setup, &token_account_a, &setup.mint_a.pubkey(), base);
+    ix.accounts[3] = instruction::AccountMeta::new(token_account_b, false);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InvalidVaultAccount,
+    )
+    .await;
+
+    // * TEST: an exchange with the vaults swapped is rejected
+    let mut ix = exchange_ix(&setup, &token_account_a, &token_account_b, base);
+    ix.accounts.swap(4, 5);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InvalidVaultAccount,
+    )
+    .await;
+
+    // * TEST: an exchange with a vault replaced by a token account of the same mint is rejected
+    let mut ix = exchange_ix(&setup, &token_account_a, &token_account_b, base);
+    ix.accounts[5] = instruction::AccountMeta::new(token_account_b, false);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InvalidVaultAccount,
+    )
+    .await;
+}
+
+/// create_program_address costs 1500 compute units, find_program_address
+/// pays it again for every bump it tries
+#[cfg(feature = "test-bpf")]
+const CREATE_PROGRAM_ADDRESS_UNITS: u64 = 1500;
+
+// compute units are only metered for the BPF build loaded by cargo test-bpf
+#[cfg(feature = "test-bpf")]
+#[tokio::test]
+async fn test_stored_bump_compute_units() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+
+    // * Measure an exchange on a booth whose booth and vault bumps are all found
+    // with the first attempt and on a booth that needs several attempts. The
+    // baseline is initializing the booth again, which re-derives the booth and
+    // vault addresses with find_program_address like exchanges did before the
+    // bumps were stored
+    let mut measurements = vec![];
+    while measurements.len() < 2 {
+        let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 1, 1, 0).await;
+        let authority = setup.authority.pubkey();
+        let (mint_a, mint_b) = (setup.mint_a.pubkey(), setup.mint_b.pubkey());
+        let (xbooth_pda, booth_bump, _, _) =
+            find_exchange_booth_address(&setup.program_id, &authority, &mint_a, &mint_b);
+        let (_, vault_a_bump) =
+            find_vault_address(&setup.program_id, &authority, &mint_a, &xbooth_pda);
+        let (_, vault_b_bump) =
+            find_vault_address(&setup.program_id, &authority, &mint_b, &xbooth_pda);
+        let attempts = [booth_bump, vault_a_bump, vault_b_bump]
+            .iter()
+            .map(|&bump| 256 - u64::from(bump))
+            .sum::<u64>();
+        let wanted = if measurements.is_empty() {
+            attempts == 3
+        } else {
+            attempts >= 6
+        };
+        if !wanted {
+            continue;
+        }
+
+        let token_account_a = setup.token_account_a.pubkey();
+        let token_account_b = setup.token_account_b.pubkey();
+        let close_ix = close_exchange_booth(
+            &setup.program_id,
+            &authority,
+            &authority,
+            &token_account_a,
+            &token_account_b,
+            &mint_a,
+            &mint_b,
+        );
+        let tx = Transaction::new_signed_with_payer(
+            &[deposit_ix(&setup, &token_account_b, &mint_b, 50 * base)],
+            Some(&authority),
+            &[&setup.authority],
+            setup.recent_blockhash,
+        );
+        setup.banks_client.process_transaction(tx).await.unwrap();
+
+        let ix = exchange_ix(&setup, &token_account_a, &token_account_b, 10 * base);
+        let exchange_units = units_consumed(&mut setup, &[ix]).await;
+
+        let tx = Transaction::new_signed_with_payer(
+            &[close_ix],
+            Some(&authority),
+            &[&setup.authority],
+            setup.recent_blockhash,
+        );
+        setup.banks_client.process_transaction(tx).await.unwrap();
+        let ix =
+            initialize_exchange_booth(&setup.program_id, &authority, &mint_a, &mint_b, 1, 1, 0);
+        let initialize_units = units_consumed(&mut setup, &[ix]).await;
+
+        measurements.push((booth_bump, attempts, exchange_units, initialize_units));
+    }
+    let (_, attempts_first, exchange_first, initialize_first) = measurements[0];
+    let (_, attempts_later, exchange_later, initialize_later) = measurements[1];
+    assert!(exchange_first > 0 && initialize_first > 0);
+
+    // * TEST: the baseline pays create_program_address for every attempt,
+    // apart from the attempts the initialization costs the same
+    let search_units = initialize_later - initialize_first;
+    let search_attempts = attempts_later - attempts_first;
+    assert!(
+        search_units.max(CREATE_PROGRAM_ADDRESS_UNITS * search_attempts)
+            - search_units.min(CREATE_PROGRAM_ADDRESS_UNITS * search_attempts)
+            < CREATE_PROGRAM_ADDRESS_UNITS,
+        "initialization does not cost {} units per attempt: {:?}",
+        CREATE_PROGRAM_ADDRESS_UNITS,
+        measurements
+    );
+
+    // * TEST: the exchange verifies the stored bumps at the same cost for any
+    // bump, while re-deriving the addresses costs the measured search_units
+    // more on the booth with the lower bumps
+    let exchange_units = exchange_later.max(exchange_first) - exchange_later.min(exchange_first);
+    assert!(
+        exchange_units < CREATE_PROGRAM_ADDRESS_UNITS,
+        "exchange units depend on the bump: {:?}",
+        measurements
+    );
+    assert!(
+        exchange_units + 3 * CREATE_PROGRAM_ADDRESS_UNITS <= search_units,
+        "storing the bumps saves less than 3 attempts: {:?}",
+        measurements
+    );
+}
+
+/// EXCHANGE_UNITS_WITHOUT_VERBOSE_LOGS bounds the compute units of an
+/// exchange followed by a rejected instruction. The verbose logs format the
+/// program id and both vaults with msg!, each pubkey costs about 12000 units
+const EXCHANGE_UNITS_WITHOUT_VERBOSE_LOGS: u64 = 40_000;
+
+#[tokio::test]
+async fn test_verbose_logs() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 1, 1, 0).await;
+    let authority = setup.authority.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix(
+            &setup,
+            &token_account_b,
+            &setup.mint_b.pubkey(),
+            50 * base,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    let ix = exchange_ix(&setup, &token_account_a, &token_account_b, 10 * base);
+    let (units, logs) = simulate(&mut setup, &[ix]).await;
+    let program_logs: Vec<&String> = logs
+        .iter()
+        .filter(|log| log.starts_with("Program log: "))
+        .collect();
+
+    // * TEST: the decoded instruction and the transfers are only logged with
+    // the verbose-logs feature, the error of the failing instruction always
+    let verbose = cfg!(feature = "verbose-logs");
+    assert_eq!(
+        program_logs
+            .iter()
+            .any(|log| log.starts_with("Program log: instruction: Exchange")),
+        verbose
+    );
+    assert_eq!(
+        program_logs
+            .iter()
+            .any(|log| log.starts_with("Program log: transfer amount: ")),
+        verbose
+    );
+    assert!(program_logs
+        .iter()
+        .any(|log| log.starts_with("Program log: unsupported instruction version")));
+
+    // * TEST: only the exchange without verbose logs stays below the bound,
+    // compute units are only metered for the BPF build
+    if cfg!(feature = "test-bpf") {
+        assert_eq!(
+            units > EXCHANGE_UNITS_WITHOUT_VERBOSE_LOGS,
+            verbose,
+            "an exchange consumed {} units",
+            units
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_emitted_events() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    // one token A buys two tokens B, with a fee of 1%
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 2, 1, 100).await;
+    let authority = setup.authority.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+
+    let close_ix = close_exchange_booth(
+        &setup.program_id,
+        &authority,
+        &authority,
+        &token_account_a,
+        &token_account_b,
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+    );
+    let instructions = [
+        deposit_ix(&setup, &token_account_b, &setup.mint_b.pubkey(), 50 * base),
+        exchange_ix(&setup, &token_account_a, &token_account_b, 10 * base),
+        withdraw_fees_ix(&setup, &authority),
+        close_ix,
+    ];
+    let (_units, logs) = simulate(&mut setup, &instructions).await;
+
+    // * TEST: every action logs its event with the transferred amounts
+    // 10 A -> 20 B - 0.2 B fee
+    assert_eq!(
+        parse_events(&setup.program_id, &logs),
+        vec![
+            XBoothEvent::Deposited {
+                booth: setup.xbooth_pda,
+                mint: setup.mint_b.pubkey(),
+                vault: setup.vault_b_pda,
+                token_account: token_account_b,
+                amount: 50 * base,
+            },
+            XBoothEvent::Exchanged {
+                booth: setup.xbooth_pda,
+                trader: authority,
+                mint_in: setup.mint_a.pubkey(),
+                mint_out: setup.mint_b.pubkey(),
+                amount_in: 10 * base,
+                amount_out: 20 * base - base / 5,
+                fee: base / 5,
+            },
+            XBoothEvent::FeesWithdrawn {
+                booth: setup.xbooth_pda,
+                admin: authority,
+                amount_a: 0,
+                amount_b: base / 5,
+            },
+            XBoothEvent::Closed {
+                booth: setup.xbooth_pda,
+                admin: authority,
+                amount_a: 10 * base,
+                amount_b: 30 * base,
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_migrate_v1_booth() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 1, 1, 0).await;
+    let authority = setup.authority.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix(
+            &setup,
+            &token_account_b,
+            &setup.mint_b.pubkey(),
+            50 * base,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * Replace the booth with the same booth in the v1 layout, which only
+    // holds the admin and the vaults
+    let xbooth = setup
+        .banks_client
+        .get_account_data_with_borsh::<ExchangeBoothAccount>(setup.xbooth_pda)
+        .await
+        .unwrap();
+    let xbooth_v1 = ExchangeBoothAccountV1 {
+        admin: xbooth.admin,
+        vault_a: xbooth.vault_a,
+        vault_b: xbooth.vault_b,
+    };
+    let rent = setup.banks_client.get_rent().await.unwrap();
+    setup.context.set_account(
+        &setup.xbooth_pda,
+        &solana_sdk::account::Account {
+            lamports: rent.minimum_balance(EXCHANGE_BOOTH_ACCOUNT_V1_LEN),
+            data: xbooth_v1.try_to_vec().unwrap(),
+            owner: setup.program_id,
+            ..solana_sdk::account::Account::default()
+        }
+        .into(),
+    );
+    assert_eq!(EXCHANGE_BOOTH_ACCOUNT_V1_LEN, 96);
+
+    // * TEST: the v1 booth cannot be used before it is migrated
+    let tx = Transaction::new_signed_with_payer(
+        &[exchange_ix(
+            &setup,
+            &token_account_a,
+            &token_account_b,
+            5 * base,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InvalidAccountVersion,
+    )
+    .await;
+
+    // * TEST: the migration needs a valid exchange rate
+    let migrate_booth_ix = |rate_numerator: u64| {
+        migrate_booth(
+            &setup.program_id,
+            &authority,
+            &setup.mint_a.pubkey(),
+            &setup.mint_b.pubkey(),
+            rate_numerator,
+            1,
+            100,
+        )
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_booth_ix(0)],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InvalidExchangeRate,
+    )
+    .await;
+
+    // * Migrate the booth with one token A buying two tokens B and a fee of 1%
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            migrate_booth_ix(2),
+            exchange_ix(&setup, &token_account_a, &token_account_b, 5 * base),
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: the booth is reallocated and rent exempt in the new layout
+    let xbooth_account = setup
+        .banks_client
+        .get_account(setup.xbooth_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(xbooth_account.data.len(), EXCHANGE_BOOTH_ACCOUNT_LEN);
+    assert_eq!(
+        xbooth_account.lamports,
+        rent.minimum_balance(EXCHANGE_BOOTH_ACCOUNT_LEN)
+    );
+    let migrated = ExchangeBoothAccount::try_from_slice(&xbooth_account.data).unwrap();
+    assert_eq!(migrated.version, EXCHANGE_BOOTH_ACCOUNT_VERSION);
+    assert!(migrated.is_initialized);
+    assert_eq!(migrated.admin, authority);
+    assert_eq!(migrated.seed_admin, authority);
+    assert_eq!(migrated.rate_numerator, 2);
+    assert_eq!(migrated.rate_denominator, 1);
+    assert_eq!(migrated.fee_bps, 100);
+
+    // * TEST: the bump seeds are found by the migration
+    assert_eq!(migrated.booth_bump, xbooth.booth_bump);
+    assert_eq!(migrated.vault_a_bump, xbooth.vault_a_bump);
+    assert_eq!(migrated.vault_b_bump, xbooth.vault_b_bump);
+
+    // * TEST: the migrated booth is usable, 5 A -> 10 B - 0.1 B fee
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_b).await,
+        (100 - 50 + 10) * base - base / 10
+    );
+    assert_eq!(migrated.fees_b, base / 10);
+
+    // * TEST: a migrated booth cannot be migrated again
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_booth_ix(2)],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InvalidAccountVersion,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_migrate_v1_booth_with_reversed_mints() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 1, 1, 0).await;
+    let authority = setup.authority.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+    let mint_a = setup.mint_a.pubkey();
+    let mint_b = setup.mint_b.pubkey();
+
+    // * Create a v1 booth seeded with mint B before mint A, v1 booths were
+    // created with the mints in the order given by the admin
+    let (xbooth_pda, xbooth_bump) = Pubkey::find_program_address(
+        &[
+            b"xbooth",
+            authority.as_ref(),
+            mint_b.as_ref(),
+            mint_a.as_ref(),
+        ],
+        &setup.program_id,
+    );
+    let (vault_b_pda, vault_b_bump) =
+        find_vault_address(&setup.program_id, &authority, &mint_b, &xbooth_pda);
+    let (vault_a_pda, vault_a_bump) =
+        find_vault_address(&setup.program_id, &authority, &mint_a, &xbooth_pda);
+    let rent = setup.banks_client.get_rent().await.unwrap();
+    for (vault, mint, amount) in [(vault_b_pda, mint_b, 50 * base), (vault_a_pda, mint_a, 0)] {
+        let mut data = vec![0; Account::LEN];
+        Account {
+            mint,
+            owner: xbooth_pda,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Account::default()
+        }
+        .pack_into_slice(&mut data);
+        setup.context.set_account(
+            &vault,
+            &solana_sdk::account::Account {
+                lamports: rent.minimum_balance(Account::LEN),
+                data,
+                owner: spl_token::id(),
+                ..solana_sdk::account::Account::default()
+            }
+            .into(),
+        );
+    }
+    let xbooth_v1 = ExchangeBoothAccountV1 {
+        admin: authority,
+        vault_a: vault_b_pda,
+        vault_b: vault_a_pda,
+    };
+    setup.context.set_account(
+        &xbooth_pda,
+        &solana_sdk::account::Account {
+            lamports: rent.minimum_balance(EXCHANGE_BOOTH_ACCOUNT_V1_LEN),
+            data: xbooth_v1.try_to_vec().unwrap(),
+            owner: setup.program_id,
+            ..solana_sdk::account::Account::default()
+        }
+        .into(),
+    );
+
+    // * Migrate with the mints in the order of the seeds of the booth, then
+    // withdraw from the vault of mint B. The withdraw builder derives the
+    // booth of the canonical mint order, so the instruction is built here
+    let withdraw_ix = Instruction {
+        program_id: setup.program_id,
+        accounts: vec![
+            instruction::AccountMeta::new(xbooth_pda, false),
+            instruction::AccountMeta::new_readonly(authority, true),
+            instruction::AccountMeta::new(token_account_b, false),
+            instruction::AccountMeta::new(vault_b_pda, false),
+            instruction::AccountMeta::new_readonly(mint_b, false),
+            instruction::AccountMeta::new_readonly(mint_a, false),
+            instruction::AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: XBoothIntruction::Withdraw { amount: 20 * base }.pack(),
+    };
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            migrate_booth(&setup.program_id, &authority, &mint_b, &mint_a, 1, 2, 0),
+            withdraw_ix,
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: the bump seeds are those of the reversed seeds
+    let migrated = setup
+        .banks_client
+        .get_account_data_with_borsh::<ExchangeBoothAccount>(xbooth_pda)
+        .await
+        .unwrap();
+    assert_eq!(migrated.vault_a, vault_b_pda);
+    assert_eq!(migrated.booth_bump, xbooth_bump);
+    assert_eq!(migrated.vault_a_bump, vault_b_bump);
+    assert_eq!(migrated.vault_b_bump, vault_a_bump);
+
+    // * TEST: the withdrawal is signed for with the reversed seeds
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &vault_b_pda).await,
+        30 * base
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_b).await,
+        120 * base
+    );
+}
+
+#[tokio::test]
+async fn test_initialize_exchange_booth_safeguards() {
+    let mut setup = setup_exchange_booth(6, 6, 100.0, 1, 1, 0).await;
+    let authority = setup.authority.pubkey();
+    let mint_a = setup.mint_a.pubkey();
+    let mint_b = setup.mint_b.pubkey();
+
+    // a new mint for the pairs that are not initialized yet
+    let mint_c = Keypair::new();
+    create_and_initialize_mint(
+        &mut setup.banks_client,
+        setup.recent_blockhash,
+        &setup.mint_authority,
+        &setup.mint_authority,
+        &mint_c,
+        &spl_token::id(),
+        &6,
+    )
+    .await
+    .unwrap();
+    let mint_c = mint_c.pubkey();
+
+    // * TEST: an initialized booth cannot be initialized again
+    let tx = Transaction::new_signed_with_payer(
+        &[initialize_exchange_booth(
+            &setup.program_id,
+            &authority,
+            &mint_a,
+            &mint_b,
+            100,
+            1,
+            0,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::AlreadyInitialized,
+    )
+    .await;
+
+    // * TEST: the token program must be the spl token program
+    let mut ix =
+        initialize_exchange_booth(&setup.program_id, &authority, &mint_a, &mint_c, 1, 1, 0);
+    ix.accounts[7] = instruction::AccountMeta::new_readonly(setup.program_id, false);
+    let tx = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::InvalidTokenProgram,
+    )
+    .await;
+
+    // * TEST: a booth needs two different mints
+    let tx = Transaction::new_signed_with_payer(
+        &[initialize_exchange_booth(
+            &setup.program_id,
+            &authority,
+            &mint_c,
+            &mint_c,
+            1,
+            1,
+            0,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(&mut setup.banks_client, tx, 0, XBoothError::IdenticalMints).await;
+
+    // * TEST: the mints must be spl token mints
+    for fake_mint in [setup.token_account_a.pubkey(), Pubkey::new_unique()] {
+        let tx = Transaction::new_signed_with_payer(
+            &[initialize_exchange_booth(
+                &setup.program_id,
+                &authority,
+                &mint_c,
+                &fake_mint,
+                1,
+                1,
+                0,
+            )],
+            Some(&authority),
+            &[&setup.authority],
+            setup.recent_blockhash,
+        );
+        assert_xbooth_error(&mut setup.banks_client, tx, 0, XBoothError::InvalidMint).await;
+    }
+
+    // * TEST: a booth for a new pair can still be initialized
+    let (mint_x, mint_y) = canonical_mints(&mint_a, &mint_c);
+    let tx = Transaction::new_signed_with_payer(
+        &[initialize_exchange_booth(
+            &setup.program_id,
+            &authority,
+            &mint_x,
+            &mint_y,
+            1,
+            1,
+            0,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_canonical_mint_order() {
+    let mut setup = setup_exchange_booth(6, 6, 100.0, 1, 1, 0).await;
+    let authority = setup.authority.pubkey();
+    let mint_a = setup.mint_a.pubkey();
+    let mint_b = setup.mint_b.pubkey();
+
+    // * TEST: the booth is found for the pair in any order
+    for (mint_x, mint_y) in [(mint_a, mint_b), (mint_b, mint_a)] {
+        let (xbooth_pda, _xbooth_bump, booth_mint_a, booth_mint_b) =
+            find_exchange_booth_address(&setup.program_id, &authority, &mint_x, &mint_y);
+        assert_eq!(xbooth_pda, setup.xbooth_pda);
+        assert_eq!((booth_mint_a, booth_mint_b), (mint_a, mint_b));
+    }
+
+    // * TEST: the booth for the swapped pair cannot be created
+    let tx = Transaction::new_signed_with_payer(
+        &[initialize_exchange_booth(
+            &setup.program_id,
+            &authority,
+            &mint_b,
+            &mint_a,
+            1,
+            1,
+            0,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(
+        &mut setup.banks_client,
+        tx,
+        0,
+        XBoothError::NonCanonicalMintOrder,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_admin_handover() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 1, 1, 0).await;
+    let authority = setup.authority.pubkey();
+    let new_admin = setup.mint_authority.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+
+    let propose_admin_ix = propose_admin(
+        &setup.program_id,
+        &authority,
+        &authority,
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+        &new_admin,
+    );
+    let accept_admin_ix = accept_admin(
+        &setup.program_id,
+        &authority,
+        &new_admin,
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+    );
+    let set_fee_ix = |admin: Pubkey| {
+        set_fee(
+            &setup.program_id,
+            &authority,
+            &admin,
+            &setup.mint_a.pubkey(),
+            &setup.mint_b.pubkey(),
+            30,
+        )
+    };
+    let old_admin_set_fee_ix = set_fee_ix(authority);
+    let new_admin_set_fee_ix = set_fee_ix(new_admin);
+    let new_admin_withdraw_ix = withdraw(
+        &setup.program_id,
+        &authority,
+        &new_admin,
+        &token_account_b,
+        &setup.mint_b.pubkey(),
+        &setup.mint_a.pubkey(),
+        10 * base,
+    );
+    let mut old_admin_accept_ix = accept_admin_ix.clone();
+    old_admin_accept_ix.accounts[1] = instruction::AccountMeta::new_readonly(authority, true);
+
+    let tx = Transaction::new_signed_with_payer(
+        &[deposit_ix(
+            &setup,
+            &token_account_b,
+            &setup.mint_b.pubkey(),
+            50 * base,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: the handover cannot be accepted before it is proposed
+    let tx = Transaction::new_signed_with_payer(
+        &[accept_admin_ix.clone()],
+        Some(&new_admin),
+        &[&setup.mint_authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(&mut setup.banks_client, tx, 0, XBoothError::InvalidOwner).await;
+
+    // * TEST: only the proposed admin can accept the handover
+    let tx = Transaction::new_signed_with_payer(
+        &[propose_admin_ix.clone(), old_admin_accept_ix],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(&mut setup.banks_client, tx, 1, XBoothError::InvalidOwner).await;
+
+    let tx = Transaction::new_signed_with_payer(
+        &[propose_admin_ix, accept_admin_ix],
+        Some(&authority),
+        &[&setup.authority, &setup.mint_authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    let xbooth = setup
+        .banks_client
+        .get_account_data_with_borsh::<ExchangeBoothAccount>(setup.xbooth_pda)
+        .await
+        .unwrap();
+    assert_eq!(xbooth.admin, new_admin);
+    assert_eq!(xbooth.seed_admin, authority);
+    assert_eq!(xbooth.pending_admin, Pubkey::default());
+
+    // * TEST: the old admin lost access to the exchange booth
+    let tx = Transaction::new_signed_with_payer(
+        &[old_admin_set_fee_ix],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(&mut setup.banks_client, tx, 0, XBoothError::InvalidOwner).await;
+
+    // * TEST: the new admin manages the booth and the vaults are still signed for
+    let tx = Transaction::new_signed_with_payer(
+        &[new_admin_set_fee_ix, new_admin_withdraw_ix],
+        Some(&new_admin),
+        &[&setup.mint_authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    let xbooth = setup
+        .banks_client
+        .get_account_data_with_borsh::<ExchangeBoothAccount>(setup.xbooth_pda)
+        .await
+        .unwrap();
+    assert_eq!(xbooth.fee_bps, 30);
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_b).await,
+        (100 - 50 + 10) * base
+    );
+
+    // * Exchange 10 A for 10 B - 0.03 B fee
+    let tx = Transaction::new_signed_with_payer(
+        &[exchange_ix(
+            &setup,
+            &token_account_a,
+            &token_account_b,
+            10 * base,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: the new admin withdraws the fees and closes the booth
+    let close_ix = close_exchange_booth(
+        &setup.program_id,
+        &authority,
+        &new_admin,
+        &token_account_a,
+        &token_account_b,
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[withdraw_fees_ix(&setup, &new_admin)],
+        Some(&new_admin),
+        &[&setup.mint_authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_b).await,
+        70 * base
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[close_ix],
+        Some(&new_admin),
+        &[&setup.mint_authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_a).await,
+        100 * base
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &token_account_b).await,
+        100 * base
+    );
+    assert!(setup
+        .banks_client
+        .get_account(setup.xbooth_pda)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_pause_exchange_booth() {
+    let mint_decimals = 6;
+    let base: u64 = 10u64.pow(mint_decimals.into());
+    // one token A buys two tokens B
+    let mut setup = setup_exchange_booth(mint_decimals, mint_decimals, 100.0, 2, 1, 0).await;
+    let authority = setup.authority.pubkey();
+    let token_account_a = setup.token_account_a.pubkey();
+    let token_account_b = setup.token_account_b.pubkey();
+
+    let pause_ix = pause(
+        &setup.program_id,
+        &authority,
+        &authority,
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+    );
+    let unpause_ix = unpause(
+        &setup.program_id,
+        &authority,
+        &authority,
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+    );
+    let foreign_unpause_ix = unpause(
+        &setup.program_id,
+        &authority,
+        &setup.mint_authority.pubkey(),
+        &setup.mint_a.pubkey(),
+        &setup.mint_b.pubkey(),
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            deposit_ix(&setup, &token_account_b, &setup.mint_b.pubkey(), 50 * base),
+            pause_ix,
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+
+    // * TEST: a paused booth rejects exchanges
+    let tx = Transaction::new_signed_with_payer(
+        &[exchange_ix(
+            &setup,
+            &token_account_a,
+            &token_account_b,
+            5 * base,
+        )],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(&mut setup.banks_client, tx, 0, XBoothError::BoothPaused).await;
+
+    // * TEST: only the admin can unpause the booth
+    let tx = Transaction::new_signed_with_payer(
+        &[foreign_unpause_ix],
+        Some(&setup.mint_authority.pubkey()),
+        &[&setup.mint_authority],
+        setup.recent_blockhash,
+    );
+    assert_xbooth_error(&mut setup.banks_client, tx, 0, XBoothError::InvalidOwner).await;
+
+    // * TEST: the admin can rebalance a paused booth
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            deposit_ix(&setup, &token_account_a, &setup.mint_a.pubkey(), 10 * base),
+            withdraw_ix(&setup, &token_account_b, &setup.mint_b.pubkey(), 20 * base),
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_a_pda).await,
+        10 * base
+    );
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_b_pda).await,
+        30 * base
+    );
+
+    // * TEST: exchanges resume after the booth is unpaused
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            unpause_ix,
+            exchange_ix(&setup, &token_account_a, &token_account_b, 5 * base),
+        ],
+        Some(&authority),
+        &[&setup.authority],
+        setup.recent_blockhash,
+    );
+    setup.banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(
+        token_balance(&mut setup.banks_client, &setup.vault_b_pda).await,
+        20 * base
+    );
+}